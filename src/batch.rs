@@ -0,0 +1,292 @@
+use crate::containers::Separated;
+use crate::{ScpiRequest, ScpiSerialize};
+
+struct BatchEntry<'a> {
+    command: Box<dyn ScpiSerialize + 'a>,
+    root: bool,
+}
+
+/// Compound program message joining multiple commands with `;`.
+///
+/// The whole batch serializes to a single message, so it is transmitted
+/// in one write. Following IEEE 488.2, a command joined with a plain `;`
+/// is interpreted relative to the header path of the previous command,
+/// whereas [`CommandBatch::root_command`] resets the path with a leading `:`.
+#[derive(Default)]
+pub struct CommandBatch<'a> {
+    entries: Vec<BatchEntry<'a>>,
+}
+
+impl<'a> CommandBatch<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a command relative to the current header path.
+    pub fn command(mut self, command: impl ScpiSerialize + 'a) -> Self {
+        self.entries.push(BatchEntry {
+            command: Box::new(command),
+            root: false,
+        });
+        self
+    }
+
+    /// Append a command with its header path reset to the root.
+    /// Common commands (`*...`) and commands already starting with `:`
+    /// are not prefixed again.
+    pub fn root_command(mut self, command: impl ScpiSerialize + 'a) -> Self {
+        self.entries.push(BatchEntry {
+            command: Box::new(command),
+            root: true,
+        });
+        self
+    }
+
+    /// Terminate the batch with a query, the response of the batch is
+    /// the response of that query.
+    pub fn query<R: ScpiRequest>(self, query: R) -> BatchQuery<'a, R> {
+        BatchQuery { batch: self, query }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl ScpiSerialize for CommandBatch<'_> {
+    fn serialize(&self, out: &mut String) {
+        for (index, entry) in self.entries.iter().enumerate() {
+            if index > 0 {
+                out.push(';');
+            }
+            let start = out.len();
+            entry.command.serialize(out);
+            if entry.root && index > 0 && !out[start..].starts_with([':', '*']) {
+                out.insert(start, ':');
+            }
+        }
+    }
+}
+
+/// A [`CommandBatch`] terminated by a query.
+pub struct BatchQuery<'a, R> {
+    batch: CommandBatch<'a>,
+    query: R,
+}
+
+impl<R: ScpiRequest> ScpiSerialize for BatchQuery<'_, R> {
+    fn serialize(&self, out: &mut String) {
+        self.batch.serialize(out);
+        if !self.batch.is_empty() {
+            out.push(';');
+        }
+        self.query.serialize(out);
+    }
+}
+
+impl<R: ScpiRequest> ScpiRequest for BatchQuery<'_, R> {
+    type Response = R::Response;
+}
+
+/// Program of commands each on its own line, e.g. a saved macro or a replayed `*LRN?` setup.
+///
+/// Unlike in a [`CommandBatch`], each line is a separate program message, so the header path
+/// is reset for each line and an erroneous command does not discard the following lines.
+/// [`Connection::execute_script`](crate::transport::Connection::execute_script) sends the script
+/// in one write, with the configured terminator between the lines. On its own, a script
+/// serializes with `\n` separated lines.
+#[derive(Default)]
+pub struct CommandScript<'a> {
+    commands: Vec<Box<dyn ScpiSerialize + 'a>>,
+}
+
+impl<'a> CommandScript<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a command as the next line.
+    pub fn command(mut self, command: impl ScpiSerialize + 'a) -> Self {
+        self.commands.push(Box::new(command));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Serialize the lines separated by `terminator`, without a trailing one.
+    pub fn serialize_with_terminator(&self, terminator: &str, out: &mut String) {
+        for (index, command) in self.commands.iter().enumerate() {
+            if index > 0 {
+                out.push_str(terminator);
+            }
+            command.serialize(out);
+        }
+    }
+}
+
+impl<'a> From<Vec<Box<dyn ScpiSerialize + 'a>>> for CommandScript<'a> {
+    fn from(commands: Vec<Box<dyn ScpiSerialize + 'a>>) -> Self {
+        Self { commands }
+    }
+}
+
+impl ScpiSerialize for CommandScript<'_> {
+    fn serialize(&self, out: &mut String) {
+        self.serialize_with_terminator("\n", out);
+    }
+}
+
+/// Tuple of queries sent as one compound message, e.g. `VOLT?;CURR?`.
+///
+/// The response, e.g. `1.5;0.1`, is deserialized into the tuple of the query responses,
+/// see [`Connection::query_batch`](crate::transport::Connection::query_batch).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryBatch<T>(pub T);
+
+macro_rules! impl_query_batch {
+    ($first:ident $(, $name:ident)*) => {
+        impl<$first: ScpiRequest, $($name: ScpiRequest),*> ScpiSerialize
+            for QueryBatch<($first, $($name,)*)>
+        {
+            #[allow(non_snake_case)]
+            fn serialize(&self, out: &mut String) {
+                let ($first, $($name,)*) = &self.0;
+                $first.serialize(out);
+                $(
+                    out.push(';');
+                    $name.serialize(out);
+                )*
+            }
+        }
+
+        impl<$first: ScpiRequest, $($name: ScpiRequest),*> ScpiRequest
+            for QueryBatch<($first, $($name,)*)>
+        {
+            type Response = Separated<($first::Response, $($name::Response,)*), ';'>;
+        }
+    };
+}
+
+impl_query_batch!(A, B);
+impl_query_batch!(A, B, C);
+impl_query_batch!(A, B, C, D);
+impl_query_batch!(A, B, C, D, E);
+impl_query_batch!(A, B, C, D, E, F);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ScpiDeserialize, impl_scpi_request, impl_scpi_serialize};
+
+    struct Voltage {
+        volts: f32,
+    }
+    impl_scpi_serialize!(Voltage, ["VOLT ", volts]);
+
+    struct Current {
+        amps: f32,
+    }
+    impl_scpi_serialize!(Current, ["CURR ", amps]);
+
+    struct Output;
+    impl_scpi_serialize!(Output, [":OUTP ON"]);
+
+    struct OperationComplete;
+    impl_scpi_serialize!(OperationComplete, ["*OPC"]);
+
+    struct VoltageQuery;
+    impl_scpi_serialize!(VoltageQuery, ["VOLT?"]);
+    impl_scpi_request!(VoltageQuery, f32);
+
+    #[test]
+    fn serialize_batch() {
+        let batch = CommandBatch::new()
+            .command(Voltage { volts: 1.5 })
+            .command(Current { amps: 0.1 });
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch.serialize_to_string(), "VOLT 1.5;CURR 0.1");
+        assert_eq!(CommandBatch::new().serialize_to_string(), "");
+    }
+
+    #[test]
+    fn serialize_root_reset() {
+        let batch = CommandBatch::new()
+            .root_command(Voltage { volts: 1.5 })
+            .root_command(Voltage { volts: 2.0 })
+            .root_command(Current { amps: 0.1 })
+            .root_command(Output)
+            .root_command(OperationComplete);
+        assert_eq!(
+            batch.serialize_to_string(),
+            "VOLT 1.5;:VOLT 2;:CURR 0.1;:OUTP ON;*OPC"
+        );
+    }
+
+    struct CurrentQuery;
+    impl_scpi_serialize!(CurrentQuery, ["CURR?"]);
+    impl_scpi_request!(CurrentQuery, f32);
+
+    #[test]
+    fn query_batch() {
+        let batch = QueryBatch((VoltageQuery, CurrentQuery, VoltageQuery));
+        assert_eq!(batch.serialize_to_string(), "VOLT?;CURR?;VOLT?");
+        let response: <QueryBatch<(VoltageQuery, CurrentQuery)> as ScpiRequest>::Response =
+            ScpiDeserialize::deserialize_complete("1.5;0.1").unwrap();
+        assert_eq!(response.0, (1.5, 0.1));
+
+        let batch = CommandBatch::new()
+            .command(Voltage { volts: 1.5 })
+            .query(QueryBatch((VoltageQuery, CurrentQuery)));
+        assert_eq!(batch.serialize_to_string(), "VOLT 1.5;VOLT?;CURR?");
+    }
+
+    #[test]
+    fn command_script() {
+        let script = CommandScript::new()
+            .command(Voltage { volts: 1.5 })
+            .command(Current { amps: 0.1 })
+            .command(Output);
+        assert_eq!(script.len(), 3);
+        let serialized = script.serialize_to_string();
+        assert_eq!(serialized, "VOLT 1.5\nCURR 0.1\n:OUTP ON");
+
+        let mut out = String::new();
+        script.serialize_with_terminator("\r\n", &mut out);
+        assert_eq!(out, "VOLT 1.5\r\nCURR 0.1\r\n:OUTP ON");
+
+        let replayed = crate::commands::LearnString(serialized);
+        let script = CommandScript::from(
+            replayed
+                .commands()
+                .into_iter()
+                .map(|command| Box::new(command) as Box<dyn ScpiSerialize>)
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(script.serialize_to_string(), replayed.0);
+    }
+
+    #[test]
+    fn serialize_trailing_query() {
+        let batch = CommandBatch::new()
+            .command(Voltage { volts: 1.5 })
+            .query(VoltageQuery);
+        assert_eq!(batch.serialize_to_string(), "VOLT 1.5;VOLT?");
+
+        let response: <BatchQuery<VoltageQuery> as ScpiRequest>::Response =
+            ScpiDeserialize::deserialize_complete("1.5").unwrap();
+        assert_eq!(response, 1.5);
+
+        let batch = CommandBatch::new().query(VoltageQuery);
+        assert_eq!(batch.serialize_to_string(), "VOLT?");
+    }
+}
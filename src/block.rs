@@ -0,0 +1,219 @@
+use crate::{Error, Result, ScpiDeserialize};
+
+/// Byte order of binary block payloads, see `FORMat:BORDer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+/// Parse the header of a definite length block, e.g. `#15` for 5 payload bytes.
+///
+/// Returns the length of the header and of the payload,
+/// or `None` if `data` is too short to contain the complete header.
+pub fn parse_block_header(data: &[u8]) -> Result<Option<(usize, usize)>> {
+    let invalid = || Error::ResponseDecoding("Expected definite length block header".to_string());
+    match data {
+        [] | [b'#'] => return Ok(None),
+        [b'#', b'1'..=b'9', ..] => {}
+        _ => return Err(invalid()),
+    }
+    let digits = (data[1] - b'0') as usize;
+    let Some(length) = data.get(2..2 + digits) else {
+        return Ok(None);
+    };
+    let length = std::str::from_utf8(length)
+        .ok()
+        .and_then(|length| length.parse().ok())
+        .ok_or_else(invalid)?;
+    Ok(Some((2 + digits, length)))
+}
+
+/// Read a complete definite length block from a byte cursor and return its payload.
+///
+/// With `consume_terminator`, a single `\n` or `\r\n` directly following the payload
+/// is consumed as well, if present. Disable it if the framing already stripped the terminator
+/// and the following byte belongs to the next field.
+pub fn read_block<'a>(input: &mut &'a [u8], consume_terminator: bool) -> Result<&'a [u8]> {
+    let (header, length) = parse_block_header(input)?.ok_or_else(|| {
+        Error::ResponseDecoding("Incomplete definite length block header".to_string())
+    })?;
+    let Some(payload) = input.get(header..header + length) else {
+        return Err(Error::ResponseDecoding(format!(
+            "Block declares {length} bytes, but contains {}",
+            input.len() - header
+        )));
+    };
+    let mut rest = &input[header + length..];
+    if consume_terminator {
+        rest = rest
+            .strip_prefix(b"\r\n")
+            .or_else(|| rest.strip_prefix(b"\n"))
+            .unwrap_or(rest);
+    }
+    *input = rest;
+    Ok(payload)
+}
+
+/// Definite length block, e.g. `#15abcde`.
+///
+/// By default a trailing terminator is consumed, see [`read_block`],
+/// so complete responses can be passed to [`ScpiDeserialize::deserialize_complete`].
+/// `Block<false>` leaves it in the input.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Block<const CONSUME_TERMINATOR: bool = true>(pub Vec<u8>);
+
+impl<const CONSUME_TERMINATOR: bool> ScpiDeserialize for Block<CONSUME_TERMINATOR> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut cursor = input.as_bytes();
+        let payload = read_block(&mut cursor, CONSUME_TERMINATOR)?;
+        let consumed = input.len() - cursor.len();
+        if !input.is_char_boundary(consumed) {
+            return Err(Error::ResponseDecoding(
+                "Block payload ends within a character".to_string(),
+            ));
+        }
+        let payload = payload.to_vec();
+        *input = &input[consumed..];
+        Ok(Self(payload))
+    }
+}
+
+macro_rules! impl_decode_real_block {
+    ($(#[$meta:meta])* $name:ident, $type:ty) => {
+        $(#[$meta])*
+        pub fn $name(bytes: &[u8], endian: Endianness) -> Result<Vec<$type>> {
+            const SIZE: usize = std::mem::size_of::<$type>();
+            if bytes.len() % SIZE != 0 {
+                return Err(Error::ResponseDecoding(format!(
+                    "Block length {} is not a multiple of the element size {SIZE}",
+                    bytes.len()
+                )));
+            }
+
+            let values = bytes
+                .chunks_exact(SIZE)
+                .map(|chunk| {
+                    let chunk = chunk.try_into().unwrap();
+                    match endian {
+                        Endianness::Little => <$type>::from_le_bytes(chunk),
+                        Endianness::Big => <$type>::from_be_bytes(chunk),
+                    }
+                })
+                .collect();
+            Ok(values)
+        }
+    };
+}
+
+impl_decode_real_block!(
+    /// Decode the payload of a `FORMat:DATA REAL,32` block into floats.
+    decode_real_block,
+    f32
+);
+impl_decode_real_block!(
+    /// Decode the payload of a `FORMat:DATA REAL,64` block into floats.
+    decode_real64_block,
+    f64
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_header() {
+        assert_eq!(parse_block_header(b"#15abcde").unwrap(), Some((3, 5)));
+        assert_eq!(parse_block_header(b"#3100").unwrap(), Some((5, 100)));
+        assert_eq!(parse_block_header(b"#3").unwrap(), None);
+        assert_eq!(parse_block_header(b"#").unwrap(), None);
+        assert_eq!(parse_block_header(b"").unwrap(), None);
+        assert!(parse_block_header(b"#0abc").is_err());
+        assert!(parse_block_header(b"#2x1").is_err());
+        assert!(parse_block_header(b"1.5").is_err());
+    }
+
+    #[test]
+    fn read_complete_block() {
+        let input = &mut &b"#15ab\ncd\n"[..];
+        assert_eq!(read_block(input, true).unwrap(), b"ab\ncd");
+        assert!(input.is_empty());
+
+        let input = &mut &b"#15abcde\r\n#10"[..];
+        assert_eq!(read_block(input, true).unwrap(), b"abcde");
+        assert_eq!(read_block(input, true).unwrap(), b"");
+        assert!(input.is_empty());
+
+        let input = &mut &b"#15abcde\n"[..];
+        assert_eq!(read_block(input, false).unwrap(), b"abcde");
+        assert_eq!(*input, b"\n");
+
+        assert!(read_block(&mut &b"#15abc"[..], true).is_err());
+        assert!(read_block(&mut &b"#2"[..], true).is_err());
+    }
+
+    #[test]
+    fn deserialize_block() {
+        assert_eq!(
+            Block::<true>::deserialize_complete("#14abcd\n").unwrap(),
+            Block(b"abcd".to_vec())
+        );
+        assert_eq!(
+            Block::<true>::deserialize_complete("#14abcd").unwrap(),
+            Block(b"abcd".to_vec())
+        );
+        assert_eq!(
+            Block::<true>::deserialize_complete("#10\n").unwrap(),
+            Block(Vec::new())
+        );
+        assert!(Block::<false>::deserialize_complete("#14abcd\n").is_err());
+
+        let input = &mut "#14abcd,1";
+        assert_eq!(
+            Block::<false>::deserialize(input).unwrap(),
+            Block(b"abcd".to_vec())
+        );
+        assert_eq!(*input, ",1");
+
+        assert!(Block::<true>::deserialize_complete("#11\u{e4}").is_err());
+    }
+
+    #[test]
+    fn decode_f32() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&1.5f32.to_le_bytes());
+        bytes.extend_from_slice(&(-2.25f32).to_le_bytes());
+        assert_eq!(
+            decode_real_block(&bytes, Endianness::Little).unwrap(),
+            vec![1.5, -2.25]
+        );
+
+        let bytes = 1.5f32.to_be_bytes();
+        assert_eq!(
+            decode_real_block(&bytes, Endianness::Big).unwrap(),
+            vec![1.5]
+        );
+    }
+
+    #[test]
+    fn decode_f64() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&1e-9f64.to_be_bytes());
+        bytes.extend_from_slice(&42.0f64.to_be_bytes());
+        assert_eq!(
+            decode_real64_block(&bytes, Endianness::Big).unwrap(),
+            vec![1e-9, 42.0]
+        );
+        assert!(
+            decode_real64_block(&[], Endianness::Little)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn decode_invalid_length() {
+        assert!(decode_real_block(&[0, 0, 0], Endianness::Little).is_err());
+        assert!(decode_real64_block(&[0; 12], Endianness::Big).is_err());
+    }
+}
@@ -0,0 +1,361 @@
+use std::fmt;
+
+use crate::headers::NumericSuffix;
+use crate::primitives::Boolean;
+use crate::status::{StandardEventStatus, StatusByte};
+use crate::strings::ScpiString;
+use crate::transport::Connection;
+use crate::{
+    Error, Result, ScpiDeserialize, ScpiSerialize, impl_scpi_request, impl_scpi_serialize,
+    match_literal, read_all, read_until, scpi_command_pair,
+};
+
+scpi_command_pair!(
+    /// Service request enable register, `*SRE`, selecting the status byte bits generating a service request.
+    pub struct ServiceRequestEnable, ServiceRequestEnableQuery => "*SRE", StatusByte
+);
+
+scpi_command_pair!(
+    /// Standard event status enable register, `*ESE`, selecting the events summarized in
+    /// [`StatusByte::EVENT_STATUS`].
+    pub struct EventStatusEnable, EventStatusEnableQuery => "*ESE", StandardEventStatus
+);
+
+/// Run the internal self-test, `*TST?`.
+pub struct SelfTest;
+impl_scpi_serialize!(SelfTest, ["*TST?"]);
+impl_scpi_request!(SelfTest, SelfTestResult);
+
+/// Result of `*TST?`, a zero code indicates success.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfTestResult {
+    Pass,
+    /// Instrument specific, non-zero error code.
+    Fail(i16),
+}
+
+impl SelfTestResult {
+    pub fn passed(&self) -> bool {
+        *self == SelfTestResult::Pass
+    }
+
+    /// Raw result code, for diagnostics.
+    pub fn code(&self) -> i16 {
+        match self {
+            SelfTestResult::Pass => 0,
+            SelfTestResult::Fail(code) => *code,
+        }
+    }
+}
+
+impl ScpiSerialize for SelfTestResult {
+    fn serialize(&self, out: &mut String) {
+        self.code().serialize(out);
+    }
+}
+
+impl ScpiDeserialize for SelfTestResult {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        Ok(match i16::deserialize(input)? {
+            0 => SelfTestResult::Pass,
+            code => SelfTestResult::Fail(code),
+        })
+    }
+}
+
+/// Query and remove the oldest entry of the error queue, `SYSTem:ERRor?`.
+pub struct SystemErrorQuery;
+impl_scpi_serialize!(SystemErrorQuery, ["SYST:ERR?"]);
+impl_scpi_request!(SystemErrorQuery, ErrorEntry);
+
+/// Entry of the error queue, e.g. `-113,"Undefined header"`.
+/// The code `0` indicates an empty queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorEntry {
+    pub code: i16,
+    pub message: String,
+}
+
+impl ErrorEntry {
+    pub fn is_error(&self) -> bool {
+        self.code != 0
+    }
+}
+
+impl fmt::Display for ErrorEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},\"{}\"", self.code, self.message)
+    }
+}
+
+impl ScpiSerialize for ErrorEntry {
+    fn serialize(&self, out: &mut String) {
+        self.code.serialize(out);
+        out.push(',');
+        ScpiString(self.message.clone()).serialize(out);
+    }
+}
+
+impl ScpiDeserialize for ErrorEntry {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let code = i16::deserialize(input)?;
+        match_literal(input, ",")?;
+        let message = ScpiString::deserialize(input)?.0;
+        Ok(Self { code, message })
+    }
+}
+
+/// Query the SCPI version the instrument complies with, `SYSTem:VERSion?`.
+pub struct SystemVersionQuery;
+impl_scpi_serialize!(SystemVersionQuery, ["SYST:VERS?"]);
+impl_scpi_request!(SystemVersionQuery, ScpiVersion);
+
+/// SCPI version formatted as `YYYY.V`, e.g. `1999.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ScpiVersion {
+    pub year: u16,
+    pub revision: u8,
+}
+
+impl ScpiSerialize for ScpiVersion {
+    fn serialize(&self, out: &mut String) {
+        self.year.serialize(out);
+        out.push('.');
+        self.revision.serialize(out);
+    }
+}
+
+impl ScpiDeserialize for ScpiVersion {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut cursor = *input;
+        let parse = |cursor: &mut &str| -> Result<Self> {
+            let year = u16::deserialize_complete(read_until(cursor, '.')?)?;
+            let revision = u8::deserialize(cursor)?;
+            Ok(Self { year, revision })
+        };
+        let version = parse(&mut cursor).map_err(|e| {
+            Error::ResponseDecoding(format!(
+                "Expected SCPI version `YYYY.V`, not matched `{input}`: {e}"
+            ))
+        })?;
+        *input = cursor;
+        Ok(version)
+    }
+}
+
+/// Switch the output of a channel on or off, `OUTPut<channel>:STATe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputState {
+    pub channel: u8,
+    pub state: Boolean,
+}
+impl_scpi_serialize!(
+    OutputState,
+    ["OUTP", channel as NumericSuffix, ":STAT ", state]
+);
+
+/// Query whether the output of a channel is on, `OUTPut<channel>:STATe?`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputStateQuery {
+    pub channel: u8,
+}
+impl_scpi_serialize!(
+    OutputStateQuery,
+    ["OUTP", channel as NumericSuffix, ":STAT?"]
+);
+impl_scpi_request!(OutputStateQuery, Boolean);
+
+/// Query the complete instrument setup, `*LRN?`.
+pub struct Learn;
+impl_scpi_serialize!(Learn, ["*LRN?"]);
+impl_scpi_request!(Learn, LearnString);
+
+/// Instrument setup as returned by `*LRN?`, a sequence of program messages
+/// restoring the current state when sent back to the instrument.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LearnString(pub String);
+
+impl LearnString {
+    /// Individual commands of the setup, split on `;` and newlines outside of quoted strings.
+    pub fn commands(&self) -> Vec<&str> {
+        let mut commands = Vec::new();
+        let mut quote = None;
+        let mut start = 0;
+        for (index, c) in self.0.char_indices() {
+            match (quote, c) {
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(open), _) if c == open => quote = None,
+                (None, ';' | '\n') => {
+                    commands.push(&self.0[start..index]);
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        commands.push(&self.0[start..]);
+        commands
+            .into_iter()
+            .map(str::trim)
+            .filter(|command| !command.is_empty())
+            .collect()
+    }
+
+    /// Restore the setup by sending each command in sequence.
+    ///
+    /// Commands are sent as individual messages, so instruments relying on
+    /// header paths relative to the previous command are not supported.
+    pub fn replay<C: Connection + ?Sized>(&self, connection: &mut C) -> Result<()> {
+        for command in self.commands() {
+            connection.write_str(command)?;
+        }
+        Ok(())
+    }
+}
+
+impl ScpiSerialize for LearnString {
+    fn serialize(&self, out: &mut String) {
+        out.push_str(&self.0);
+    }
+}
+
+/// The learn string is the complete response, including any `;`.
+impl ScpiDeserialize for LearnString {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        read_all(input).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::ScpiRequest;
+
+    #[derive(Default)]
+    struct Recorder {
+        sent: Vec<String>,
+    }
+
+    impl Connection for Recorder {
+        fn send(&mut self, message: &str) -> Result<()> {
+            self.sent.push(message.to_string());
+            Ok(())
+        }
+
+        fn receive_bytes(&mut self) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+
+        fn timeout(&self) -> Duration {
+            Duration::ZERO
+        }
+
+        fn set_timeout(&mut self, _timeout: Duration) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn register_enable() {
+        let command = ServiceRequestEnable {
+            value: StatusByte::EVENT_STATUS | StatusByte::MESSAGE_AVAILABLE,
+        };
+        assert_eq!(command.serialize_to_string(), "*SRE 48");
+        assert_eq!(ServiceRequestEnableQuery.serialize_to_string(), "*SRE?");
+
+        let command = EventStatusEnable {
+            value: StandardEventStatus::OPERATION_COMPLETE,
+        };
+        assert_eq!(command.serialize_to_string(), "*ESE 1");
+        let response: <EventStatusEnableQuery as ScpiRequest>::Response =
+            ScpiDeserialize::deserialize_complete("61").unwrap();
+        assert!(response.contains(StandardEventStatus::COMMAND_ERROR));
+    }
+
+    #[test]
+    fn self_test() {
+        assert_eq!(SelfTest.serialize_to_string(), "*TST?");
+        let result = SelfTestResult::deserialize_complete("+0").unwrap();
+        assert!(result.passed());
+        assert_eq!(result.code(), 0);
+        let result = SelfTestResult::deserialize_complete("-330").unwrap();
+        assert_eq!(result, SelfTestResult::Fail(-330));
+        assert_eq!(result.code(), -330);
+        crate::assert_scpi_roundtrip!(SelfTestResult::Fail(7));
+    }
+
+    #[test]
+    fn error_entry() {
+        let entry =
+            ErrorEntry::deserialize_complete("-113,\"Undefined header; \"\"X\"\"\"").unwrap();
+        assert_eq!(entry.code, -113);
+        assert_eq!(entry.message, "Undefined header; \"X\"");
+        assert!(entry.is_error());
+        crate::assert_scpi_roundtrip!(entry);
+
+        let entry = ErrorEntry::deserialize_complete("+0,\"No error\"").unwrap();
+        assert!(!entry.is_error());
+        assert_eq!(entry.to_string(), "0,\"No error\"");
+        assert!(ErrorEntry::deserialize_complete("-113").is_err());
+    }
+
+    #[test]
+    fn scpi_version() {
+        let version = ScpiVersion::deserialize_complete("1999.0").unwrap();
+        assert_eq!(
+            version,
+            ScpiVersion {
+                year: 1999,
+                revision: 0
+            }
+        );
+        crate::assert_scpi_roundtrip!(version);
+        assert!(version < ScpiVersion::deserialize_complete("2001.1").unwrap());
+
+        for malformed in ["1999", "1999.", "19a9.0", ".0", "99999.0"] {
+            let error = ScpiVersion::deserialize_complete(malformed).unwrap_err();
+            assert!(error.to_string().contains("YYYY.V"), "{malformed}: {error}");
+        }
+    }
+
+    #[test]
+    fn output_state() {
+        let command = OutputState {
+            channel: 2,
+            state: true.into(),
+        };
+        assert_eq!(command.serialize_to_string(), "OUTP2:STAT ON");
+        assert_eq!(
+            OutputStateQuery { channel: 1 }.serialize_to_string(),
+            "OUTP1:STAT?"
+        );
+    }
+
+    #[test]
+    fn learn() {
+        assert_eq!(Learn.serialize_to_string(), "*LRN?");
+        let setup: <Learn as ScpiRequest>::Response = ScpiDeserialize::deserialize_complete(
+            ":SOUR:VOLT 1.5;:SOUR:CURR 0.1\n:DISP:TEXT \"A;B\";:OUTP ON;",
+        )
+        .unwrap();
+        assert_eq!(
+            setup.commands(),
+            vec![
+                ":SOUR:VOLT 1.5",
+                ":SOUR:CURR 0.1",
+                ":DISP:TEXT \"A;B\"",
+                ":OUTP ON"
+            ]
+        );
+        assert!(LearnString::default().commands().is_empty());
+    }
+
+    #[test]
+    fn replay() {
+        let setup = LearnString(":VOLT 1.5;:OUTP ON".to_string());
+        let mut recorder = Recorder::default();
+        setup.replay(&mut recorder).unwrap();
+        assert_eq!(recorder.sent, vec![":VOLT 1.5", ":OUTP ON"]);
+    }
+}
@@ -0,0 +1,181 @@
+use std::marker::PhantomData;
+
+use crate::primitives::ExtendedFloat;
+use crate::{Result, ScpiDeserialize, ScpiSerialize};
+
+/// Complex value in the `real,imag` form, e.g. impedances or S-parameters.
+///
+/// Components may be non-finite, see [`ExtendedFloat`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    /// Construct from magnitude and phase in radians.
+    /// A zero phase yields a zero imaginary part, even for an infinite magnitude.
+    pub fn from_polar(magnitude: f64, phase: f64) -> Self {
+        if phase == 0.0 {
+            return Self::new(magnitude, 0.0);
+        }
+        Self::new(magnitude * phase.cos(), magnitude * phase.sin())
+    }
+
+    pub fn magnitude(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// Phase in radians.
+    pub fn phase(&self) -> f64 {
+        self.im.atan2(self.re)
+    }
+}
+
+impl ScpiSerialize for Complex {
+    fn serialize(&self, out: &mut String) {
+        (ExtendedFloat(self.re), ExtendedFloat(self.im)).serialize(out);
+    }
+}
+
+impl ScpiDeserialize for Complex {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let (re, im) = <(ExtendedFloat, ExtendedFloat)>::deserialize(input)?;
+        Ok(Self::new(re.0, im.0))
+    }
+}
+
+/// Unit of the phase of [`Polar`].
+pub trait AngleUnit {
+    fn to_radians(angle: f64) -> f64;
+    fn from_radians(angle: f64) -> f64;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Degrees;
+
+impl AngleUnit for Degrees {
+    fn to_radians(angle: f64) -> f64 {
+        angle.to_radians()
+    }
+
+    fn from_radians(angle: f64) -> f64 {
+        angle.to_degrees()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Radians;
+
+impl AngleUnit for Radians {
+    fn to_radians(angle: f64) -> f64 {
+        angle
+    }
+
+    fn from_radians(angle: f64) -> f64 {
+        angle
+    }
+}
+
+/// Complex value in the polar `magnitude,phase` form, with the phase in unit `A`.
+///
+/// Components may be non-finite, see [`ExtendedFloat`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Polar<A: AngleUnit = Degrees> {
+    pub value: Complex,
+    unit: PhantomData<A>,
+}
+
+impl<A: AngleUnit> Polar<A> {
+    pub fn new(value: Complex) -> Self {
+        Self {
+            value,
+            unit: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> Complex {
+        self.value
+    }
+}
+
+impl<A: AngleUnit> From<Complex> for Polar<A> {
+    fn from(value: Complex) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<A: AngleUnit> ScpiSerialize for Polar<A> {
+    fn serialize(&self, out: &mut String) {
+        let magnitude = ExtendedFloat(self.value.magnitude());
+        let phase = ExtendedFloat(A::from_radians(self.value.phase()));
+        (magnitude, phase).serialize(out);
+    }
+}
+
+impl<A: AngleUnit> ScpiDeserialize for Polar<A> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let (magnitude, phase) = <(ExtendedFloat, ExtendedFloat)>::deserialize(input)?;
+        Ok(Self::new(Complex::from_polar(
+            magnitude.0,
+            A::to_radians(phase.0),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::numeric::approx_eq;
+
+    #[test]
+    fn rectangular() {
+        let value = Complex::deserialize_complete("50.2,-1.5E-1").unwrap();
+        assert_eq!(value, Complex::new(50.2, -0.15));
+        assert_eq!(value.serialize_to_string(), "50.2,-0.15");
+        crate::assert_scpi_roundtrip!(Complex::new(-1.0, 2.5));
+
+        let value = Complex::deserialize_complete("NAN,-INF").unwrap();
+        assert!(value.re.is_nan());
+        assert_eq!(value.im, f64::NEG_INFINITY);
+
+        assert_eq!(
+            Complex::new(f64::INFINITY, 1.0).serialize_to_string(),
+            "INF,1"
+        );
+        assert!(Complex::deserialize_complete("INFO,1").is_err());
+        assert!(Complex::deserialize_complete("1.5").is_err());
+        assert!(Complex::deserialize_complete("1.5,2,3").is_err());
+    }
+
+    #[test]
+    fn polar() {
+        let value = Polar::<Degrees>::deserialize_complete("2,90")
+            .unwrap()
+            .value;
+        assert!(approx_eq(value.re, 0.0, 1e-12));
+        assert!(approx_eq(value.im, 2.0, 1e-12));
+
+        let value = Polar::<Radians>::deserialize_complete("1,-3.141592653589793")
+            .unwrap()
+            .value;
+        assert!(approx_eq(value.re, -1.0, 1e-12));
+        assert!(approx_eq(value.im, 0.0, 1e-12));
+
+        let polar = Polar::<Degrees>::new(Complex::new(0.0, -3.0));
+        assert_eq!(polar.serialize_to_string(), "3,-90");
+
+        let value = Polar::<Degrees>::deserialize_complete("INF,0")
+            .unwrap()
+            .value;
+        assert_eq!(value, Complex::new(f64::INFINITY, 0.0));
+
+        fn assert_value<T: std::fmt::Debug + Clone + Copy + PartialEq>() {}
+        assert_value::<Polar<Degrees>>();
+        assert_value::<Polar<Radians>>();
+    }
+}
@@ -0,0 +1,589 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use crate::{Error, Result, ScpiDeserialize, ScpiSerialize};
+
+fn serialize_list<T: ScpiSerialize>(elements: &[T], delimiter: char, out: &mut String) {
+    for (index, element) in elements.iter().enumerate() {
+        if index > 0 {
+            out.push(delimiter);
+        }
+        element.serialize(out);
+    }
+}
+
+fn deserialize_list<T: ScpiDeserialize>(input: &mut &str, delimiter: char) -> Result<Vec<T>> {
+    let mut result = Vec::new();
+    if input.is_empty() {
+        return Ok(result);
+    }
+    loop {
+        let before = input.len();
+        result.push(T::deserialize(input)?);
+        match input.strip_prefix(delimiter) {
+            Some(rest) => *input = rest,
+            None if input.len() >= before
+                && !input.is_empty()
+                && !input.starts_with([',', ';']) =>
+            {
+                return Err(no_progress(input));
+            }
+            None => return Ok(result),
+        }
+    }
+}
+
+/// An element consumed nothing, and neither a separator nor the end of input follows,
+/// e.g. an empty placeholder in front of garbled data. Reported instead of
+/// silently ending the list, or repeating the element without bounds.
+fn no_progress(input: &str) -> Error {
+    Error::ResponseDecoding(format!(
+        "List element consumed no input, not matched `{input}`"
+    ))
+}
+
+/// Comma separated list, e.g. `1,2,3`.
+impl<T: ScpiSerialize> ScpiSerialize for [T] {
+    fn serialize(&self, out: &mut String) {
+        serialize_list(self, ',', out);
+    }
+}
+
+/// Comma separated list, e.g. `1,2,3`.
+impl<T: ScpiSerialize> ScpiSerialize for Vec<T> {
+    fn serialize(&self, out: &mut String) {
+        self.as_slice().serialize(out);
+    }
+}
+
+/// Comma separated list, e.g. `1,2,3`.
+/// Parsing stops at the first element not followed by a comma,
+/// an empty input yields an empty list. An element consuming no input, e.g. an empty
+/// placeholder, must be followed by a comma, a `;` of an outer list or the end of input,
+/// otherwise parsing fails.
+impl<T: ScpiDeserialize> ScpiDeserialize for Vec<T> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        deserialize_list(input, ',')
+    }
+}
+
+/// List with a custom delimiter, e.g. `Delimited<u8, ';'>` for `1;2;3`.
+/// Behaves like `Vec<T>` otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Delimited<T, const DELIMITER: char>(pub Vec<T>);
+
+impl<T, const DELIMITER: char> From<Vec<T>> for Delimited<T, DELIMITER> {
+    fn from(value: Vec<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: ScpiSerialize, const DELIMITER: char> ScpiSerialize for Delimited<T, DELIMITER> {
+    fn serialize(&self, out: &mut String) {
+        serialize_list(&self.0, DELIMITER, out);
+    }
+}
+
+impl<T: ScpiDeserialize, const DELIMITER: char> ScpiDeserialize for Delimited<T, DELIMITER> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        deserialize_list(input, DELIMITER).map(Self)
+    }
+}
+
+/// Comma separated list preceded by its number of elements, e.g. `3,1.0,2.0,3.0`.
+///
+/// Deserialization reads exactly the announced number of elements and fails
+/// if fewer arrive or a further element follows. An empty list is `0`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CountedList<T>(pub Vec<T>);
+
+impl<T> From<Vec<T>> for CountedList<T> {
+    fn from(value: Vec<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: ScpiSerialize> ScpiSerialize for CountedList<T> {
+    fn serialize(&self, out: &mut String) {
+        (self.0.len() as u64).serialize(out);
+        for element in &self.0 {
+            out.push(',');
+            element.serialize(out);
+        }
+    }
+}
+
+impl<T: ScpiDeserialize> ScpiDeserialize for CountedList<T> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut cursor = *input;
+        let count = u32::deserialize(&mut cursor)?;
+        let mut elements = Vec::new();
+        for index in 0..count {
+            cursor = cursor.strip_prefix(',').ok_or_else(|| {
+                Error::ResponseDecoding(format!(
+                    "Expected {count} elements, found {index} in front of `{cursor}`"
+                ))
+            })?;
+            elements.push(T::deserialize(&mut cursor)?);
+        }
+        if cursor.starts_with(',') {
+            return Err(Error::ResponseDecoding(format!(
+                "Expected {count} elements, found more in front of `{cursor}`"
+            )));
+        }
+        *input = cursor;
+        Ok(Self(elements))
+    }
+}
+
+/// Loosely structured settings, e.g. `GAIN=10,MODE="A,B"`, as pairs of key and value.
+///
+/// Pairs are separated by commas outside of double quoted strings,
+/// each value is deserialized completely as `T`. Values are passed on as received,
+/// so `KeyValues<String>` keeps the quotes of `MODE="A,B"`, use
+/// [`ScpiString`](crate::strings::ScpiString) as `T` to unquote them.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct KeyValues<T>(pub Vec<(String, T)>);
+
+impl<T> KeyValues<T> {
+    pub fn get(&self, key: &str) -> Option<&T> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Later pairs take precedence over earlier ones with the same key.
+    pub fn into_map(self) -> HashMap<String, T> {
+        self.0.into_iter().collect()
+    }
+}
+
+impl<T: ScpiSerialize> ScpiSerialize for KeyValues<T> {
+    fn serialize(&self, out: &mut String) {
+        for (index, (key, value)) in self.0.iter().enumerate() {
+            if index > 0 {
+                out.push(',');
+            }
+            out.push_str(key);
+            out.push('=');
+            value.serialize(out);
+        }
+    }
+}
+
+fn split_unquoted(input: &str, delimiter: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (index, c) in input.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if c == delimiter && !quoted {
+            parts.push(&input[start..index]);
+            start = index + c.len_utf8();
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+/// Consumes the complete input.
+impl<T: ScpiDeserialize> ScpiDeserialize for KeyValues<T> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut pairs = Vec::new();
+        if !input.is_empty() {
+            for pair in split_unquoted(input, ',') {
+                let invalid = |reason: String| {
+                    Error::ResponseDecoding(format!("Invalid pair `{pair}`: {reason}"))
+                };
+                let (key, value) = pair
+                    .split_once('=')
+                    .ok_or_else(|| invalid("Expected `=`".to_string()))?;
+                let value = T::deserialize_complete(value).map_err(|e| invalid(e.to_string()))?;
+                pairs.push((key.to_string(), value));
+            }
+        }
+        *input = "";
+        Ok(Self(pairs))
+    }
+}
+
+/// Tuple with its fields separated by `SEPARATOR` instead of a comma,
+/// e.g. `1.5;0.1` for `Separated<(f32, f32), ';'>` as in responses to compound queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Separated<T, const SEPARATOR: char>(pub T);
+
+macro_rules! impl_tuple {
+    (@serialize $self:ident, $out:ident, $separator:expr, $first:ident $(, $name:ident)*) => {{
+        let ($first, $($name,)*) = $self;
+        $first.serialize($out);
+        $(
+            $out.push($separator);
+            $name.serialize($out);
+        )*
+    }};
+
+    (@deserialize $input:ident, $separator:expr, $first:ident $(, $name:ident)*) => {{
+        let $first = $first::deserialize($input)?;
+        $(
+            match $input.strip_prefix($separator) {
+                Some(rest) => *$input = rest,
+                None => {
+                    return Err(Error::ResponseDecoding(format!(
+                        "Expected literal `{}` not matched `{}`",
+                        $separator, $input
+                    )));
+                }
+            }
+            let $name = $name::deserialize($input)?;
+        )*
+        ($first, $($name,)*)
+    }};
+
+    ($($name:ident),+) => {
+        /// Comma separated fields, e.g. `1,ON` for `(u8, Switch)`.
+        impl<$($name: ScpiSerialize),+> ScpiSerialize for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn serialize(&self, out: &mut String) {
+                impl_tuple!(@serialize self, out, ',', $($name),+)
+            }
+        }
+
+        /// Comma separated fields, e.g. `1,ON` for `(u8, Switch)`.
+        impl<$($name: ScpiDeserialize),+> ScpiDeserialize for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn deserialize(input: &mut &str) -> Result<Self> {
+                Ok(impl_tuple!(@deserialize input, ',', $($name),+))
+            }
+        }
+
+        impl<$($name: ScpiSerialize,)+ const SEPARATOR: char> ScpiSerialize
+            for Separated<($($name,)+), SEPARATOR>
+        {
+            #[allow(non_snake_case)]
+            fn serialize(&self, out: &mut String) {
+                let fields = &self.0;
+                impl_tuple!(@serialize fields, out, SEPARATOR, $($name),+)
+            }
+        }
+
+        impl<$($name: ScpiDeserialize,)+ const SEPARATOR: char> ScpiDeserialize
+            for Separated<($($name,)+), SEPARATOR>
+        {
+            #[allow(non_snake_case)]
+            fn deserialize(input: &mut &str) -> Result<Self> {
+                Ok(Self(impl_tuple!(@deserialize input, SEPARATOR, $($name),+)))
+            }
+        }
+    };
+}
+
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+impl_tuple!(A, B, C, D, E);
+impl_tuple!(A, B, C, D, E, F);
+
+/// Lazily deserialize the elements of a comma separated response, e.g. to
+/// fold or downsample large traces without allocating the whole list.
+///
+/// Ends at the end of the input. A malformed element, or trailing data not
+/// separated by a comma, is yielded as error item, after which iteration stops.
+pub struct ScpiValueIter<'a, T> {
+    input: &'a str,
+    done: bool,
+    element: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> ScpiValueIter<'a, T> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            done: input.is_empty(),
+            element: PhantomData,
+        }
+    }
+
+    /// Input not consumed yet.
+    pub fn remaining(&self) -> &'a str {
+        self.input
+    }
+}
+
+impl<T: ScpiDeserialize> Iterator for ScpiValueIter<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let before = self.input.len();
+        let result = T::deserialize(&mut self.input).and_then(|element| {
+            match self.input.strip_prefix(',') {
+                Some(rest) => self.input = rest,
+                None if self.input.is_empty() => self.done = true,
+                None if self.input.len() >= before => return Err(no_progress(self.input)),
+                None => {
+                    return Err(Error::ResponseDecoding(format!(
+                        "Expected `,` or end of input, not matched `{}`",
+                        self.input
+                    )));
+                }
+            }
+            Ok(element)
+        });
+        if result.is_err() {
+            self.done = true;
+        }
+        Some(result)
+    }
+}
+
+impl<T: ScpiDeserialize> std::iter::FusedIterator for ScpiValueIter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strings::ScpiString;
+    use crate::wrappers::WithPlaceholder;
+
+    /// Element consuming nothing, as a garbled placeholder would.
+    #[derive(Debug)]
+    struct Nothing;
+
+    impl ScpiDeserialize for Nothing {
+        fn deserialize(_: &mut &str) -> Result<Self> {
+            Ok(Nothing)
+        }
+    }
+
+    #[test]
+    fn zero_progress() {
+        assert_eq!(Vec::<Nothing>::deserialize_complete(",,").unwrap().len(), 3);
+        let error = Vec::<Nothing>::deserialize(&mut "garbage").unwrap_err();
+        assert!(error.to_string().contains("consumed no input"), "{error}");
+        let error = Delimited::<Nothing, ';'>::deserialize(&mut ";x").unwrap_err();
+        assert!(error.to_string().contains("consumed no input"), "{error}");
+
+        // a trailing empty element ends the list in front of an outer separator
+        let input = &mut ";5";
+        assert_eq!(Vec::<Nothing>::deserialize(input).unwrap().len(), 1);
+        assert_eq!(*input, ";5");
+        let (list, last) =
+            Separated::<(Vec<WithPlaceholder<u8>>, u8), ';'>::deserialize_complete("1,;5")
+                .unwrap()
+                .0;
+        assert_eq!(
+            list.into_iter()
+                .map(WithPlaceholder::into_inner)
+                .collect::<Vec<_>>(),
+            vec![Some(1), None]
+        );
+        assert_eq!(last, 5);
+
+        let mut iter = ScpiValueIter::<Nothing>::new("garbage");
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn serialize_vec() {
+        assert_eq!(vec![1u8, 2, 3].serialize_to_string(), "1,2,3");
+        assert_eq!(vec![1.5f32].serialize_to_string(), "1.5");
+        assert_eq!(Vec::<u8>::new().serialize_to_string(), "");
+    }
+
+    struct Points<'a> {
+        offset: &'a f32,
+        values: &'a [u8],
+    }
+    crate::impl_scpi_serialize!(Points<'_>, ["POIN ", offset, ",", values]);
+
+    #[test]
+    fn serialize_slice() {
+        let values = [1u8, 2, 3];
+        assert_eq!(values[..].serialize_to_string(), "1,2,3");
+        assert_eq!(vec![&1u8, &2].serialize_to_string(), "1,2");
+    }
+
+    #[test]
+    fn serialize_borrowed_fields() {
+        let offset = 1.5f32;
+        let values = [1u8, 2, 3];
+        let command = Points {
+            offset: &offset,
+            values: &values[1..],
+        };
+        assert_eq!(command.serialize_to_string(), "POIN 1.5,2,3");
+        assert_eq!(Some(&offset).serialize_to_string(), "1.5");
+    }
+
+    #[test]
+    fn deserialize_vec() {
+        assert_eq!(
+            Vec::<u8>::deserialize_complete("1,2,3").unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            Vec::<f32>::deserialize_complete("-1.5").unwrap(),
+            vec![-1.5]
+        );
+        assert!(Vec::<u8>::deserialize_complete("").unwrap().is_empty());
+        assert!(Vec::<u8>::deserialize_complete("1,").is_err());
+        assert!(Vec::<u8>::deserialize_complete("1,x").is_err());
+
+        let input = &mut "1,2;3";
+        assert_eq!(Vec::<u8>::deserialize(input).unwrap(), vec![1, 2]);
+        assert_eq!(*input, ";3");
+    }
+
+    #[test]
+    fn counted_list() {
+        let list: CountedList<f64> = vec![1.0, 2.5, -3.0].into();
+        assert_eq!(list.serialize_to_string(), "3,1,2.5,-3");
+        crate::assert_scpi_roundtrip!(list);
+        assert_eq!(
+            CountedList::<f64>::deserialize_complete("3,1.0,2.0,3.0").unwrap(),
+            CountedList(vec![1.0, 2.0, 3.0])
+        );
+
+        assert_eq!(CountedList::<u8>::default().serialize_to_string(), "0");
+        let input = &mut "0;1";
+        assert!(CountedList::<u8>::deserialize(input).unwrap().0.is_empty());
+        assert_eq!(*input, ";1");
+
+        for mismatched in ["3,1,2", "2,1,2,3", "1", "0,1", "x,1"] {
+            let input = &mut &*mismatched;
+            assert!(
+                CountedList::<u8>::deserialize(input).is_err(),
+                "{mismatched}"
+            );
+            assert_eq!(*input, mismatched);
+        }
+        let error = CountedList::<u8>::deserialize_complete("3,1,2").unwrap_err();
+        assert!(error.to_string().contains("Expected 3 elements, found 2"));
+    }
+
+    #[test]
+    fn serialize_delimited() {
+        let list: Delimited<u8, ';'> = vec![1, 2, 3].into();
+        assert_eq!(list.serialize_to_string(), "1;2;3");
+        let list: Delimited<u8, ' '> = vec![1, 2].into();
+        assert_eq!(list.serialize_to_string(), "1 2");
+    }
+
+    #[test]
+    fn deserialize_delimited() {
+        assert_eq!(
+            Delimited::<u8, ','>::deserialize_complete("1,2,3").unwrap(),
+            Delimited(vec![1, 2, 3])
+        );
+        assert_eq!(
+            Delimited::<u8, ';'>::deserialize_complete("1;2;3").unwrap(),
+            Delimited(vec![1, 2, 3])
+        );
+        assert_eq!(
+            Delimited::<f32, ';'>::deserialize_complete("1.5").unwrap(),
+            Delimited(vec![1.5])
+        );
+        assert!(Delimited::<u8, ';'>::deserialize_complete("1,2").is_err());
+        assert!(Delimited::<u8, ';'>::deserialize_complete("1;").is_err());
+
+        let input = &mut "1;2,3";
+        let list = Delimited::<u8, ';'>::deserialize(input).unwrap();
+        assert_eq!(list.0, vec![1, 2]);
+        assert_eq!(*input, ",3");
+    }
+
+    #[test]
+    fn tuples() {
+        assert_eq!((1u8, -2.5f32).serialize_to_string(), "1,-2.5");
+        assert_eq!(
+            <(u8, f32, i16)>::deserialize_complete("1,-2.5,3").unwrap(),
+            (1, -2.5, 3)
+        );
+        assert!(<(u8, u8)>::deserialize_complete("1").is_err());
+        assert!(<(u8, u8)>::deserialize_complete("1,2,3").is_err());
+
+        assert_eq!(
+            Separated::<_, ';'>((1.5f32, 0.1f32, 2u8)).serialize_to_string(),
+            "1.5;0.1;2"
+        );
+        assert_eq!(
+            Separated::<(f32, f32), ';'>::deserialize_complete("1.5;0.1")
+                .unwrap()
+                .0,
+            (1.5, 0.1)
+        );
+        assert!(Separated::<(f32, f32), ';'>::deserialize_complete("1.5,0.1").is_err());
+
+        // a trailing list takes all remaining elements
+        assert_eq!(
+            <(u8, Vec<u8>)>::deserialize_complete("1,2,3").unwrap(),
+            (1, vec![2, 3])
+        );
+    }
+
+    #[test]
+    fn key_values() {
+        let settings = KeyValues::<f32>::deserialize_complete("GAIN=10,OFFS=-0.5").unwrap();
+        assert_eq!(
+            settings.0,
+            vec![("GAIN".to_string(), 10.0), ("OFFS".to_string(), -0.5)]
+        );
+        assert_eq!(settings.get("OFFS"), Some(&-0.5));
+        assert_eq!(settings.get("MODE"), None);
+        assert_eq!(settings.serialize_to_string(), "GAIN=10,OFFS=-0.5");
+        assert_eq!(settings.into_map()["GAIN"], 10.0);
+
+        let settings = KeyValues::<String>::deserialize_complete("NAME=\"A,B\",MODE=AUTO").unwrap();
+        assert_eq!(settings.get("NAME").unwrap(), "\"A,B\"");
+        assert_eq!(settings.get("MODE").unwrap(), "AUTO");
+
+        let settings =
+            KeyValues::<ScpiString>::deserialize_complete("NAME=\"A,B\",UNIT=\"V\"").unwrap();
+        assert_eq!(settings.get("NAME").unwrap().0, "A,B");
+        assert_eq!(settings.get("UNIT").unwrap().0, "V");
+
+        assert!(
+            KeyValues::<u8>::deserialize_complete("")
+                .unwrap()
+                .0
+                .is_empty()
+        );
+
+        let error = KeyValues::<u8>::deserialize_complete("A=1,B=X").unwrap_err();
+        assert!(error.to_string().contains("`B=X`"), "{error}");
+        assert!(KeyValues::<u8>::deserialize_complete("A=1,B").is_err());
+    }
+
+    #[test]
+    fn value_iter() {
+        let values: Vec<f32> = ScpiValueIter::new("1.5,2,-3")
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(values, vec![1.5, 2.0, -3.0]);
+
+        let sum: u32 = ScpiValueIter::<u32>::new("1,2,3,4")
+            .map(|value| value.unwrap())
+            .sum();
+        assert_eq!(sum, 10);
+
+        assert_eq!(ScpiValueIter::<u8>::new("").count(), 0);
+
+        let mut iter = ScpiValueIter::<u8>::new("1,X,3");
+        assert_eq!(iter.next().unwrap().unwrap(), 1);
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+
+        let mut iter = ScpiValueIter::<u8>::new("1;2");
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+
+        let mut iter = ScpiValueIter::<u8>::new("1,");
+        assert_eq!(iter.next().unwrap().unwrap(), 1);
+        assert_eq!(iter.remaining(), "");
+        assert!(iter.next().unwrap().is_err());
+    }
+}
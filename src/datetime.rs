@@ -0,0 +1,201 @@
+use crate::{Error, Result, ScpiDeserialize, ScpiSerialize, match_literal};
+
+/// Calendar date as used by `SYSTem:DATE`, e.g. `2024,3,15`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+/// Time of day as used by `SYSTem:TIME`, e.g. `13,45,2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+/// Combined date and time, serialized as `2024,3,15,13,45,2`.
+/// Deserialization additionally accepts the ISO-like form `2024-03-15T13:45:02`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    pub date: Date,
+    pub time: Time,
+}
+
+fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn check_range(name: &str, value: u8, min: u8, max: u8) -> Result<()> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(Error::ResponseDecoding(format!(
+            "{name} {value} is not within [{min}, {max}]"
+        )))
+    }
+}
+
+impl Date {
+    pub fn new(year: u16, month: u8, day: u8) -> Result<Self> {
+        check_range("Month", month, 1, 12)?;
+        check_range("Day", day, 1, days_in_month(year, month))?;
+        Ok(Self { year, month, day })
+    }
+
+    fn deserialize_with(input: &mut &str, separator: &'static str) -> Result<Self> {
+        let year = u16::deserialize(input)?;
+        match_literal(input, separator)?;
+        let month = u8::deserialize(input)?;
+        match_literal(input, separator)?;
+        let day = u8::deserialize(input)?;
+        Self::new(year, month, day)
+    }
+}
+
+impl Time {
+    pub fn new(hour: u8, minute: u8, second: u8) -> Result<Self> {
+        check_range("Hour", hour, 0, 23)?;
+        check_range("Minute", minute, 0, 59)?;
+        check_range("Second", second, 0, 59)?;
+        Ok(Self {
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    fn deserialize_with(input: &mut &str, separator: &'static str) -> Result<Self> {
+        let hour = u8::deserialize(input)?;
+        match_literal(input, separator)?;
+        let minute = u8::deserialize(input)?;
+        match_literal(input, separator)?;
+        let second = u8::deserialize(input)?;
+        Self::new(hour, minute, second)
+    }
+}
+
+impl ScpiSerialize for Date {
+    fn serialize(&self, out: &mut String) {
+        self.year.serialize(out);
+        out.push(',');
+        self.month.serialize(out);
+        out.push(',');
+        self.day.serialize(out);
+    }
+}
+
+impl ScpiDeserialize for Date {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        Self::deserialize_with(input, ",")
+    }
+}
+
+impl ScpiSerialize for Time {
+    fn serialize(&self, out: &mut String) {
+        self.hour.serialize(out);
+        out.push(',');
+        self.minute.serialize(out);
+        out.push(',');
+        self.second.serialize(out);
+    }
+}
+
+impl ScpiDeserialize for Time {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        Self::deserialize_with(input, ",")
+    }
+}
+
+impl ScpiSerialize for DateTime {
+    fn serialize(&self, out: &mut String) {
+        self.date.serialize(out);
+        out.push(',');
+        self.time.serialize(out);
+    }
+}
+
+impl ScpiDeserialize for DateTime {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut cursor = *input;
+        if let Ok(date) = Date::deserialize_with(&mut cursor, "-") {
+            if match_literal(&mut cursor, "T").is_err() {
+                match_literal(&mut cursor, " ")?;
+            }
+            let time = Time::deserialize_with(&mut cursor, ":")?;
+            *input = cursor;
+            return Ok(Self { date, time });
+        }
+
+        let date = Date::deserialize(input)?;
+        match_literal(input, ",")?;
+        let time = Time::deserialize(input)?;
+        Ok(Self { date, time })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date() {
+        let date = Date::deserialize_complete("2024,3,15").unwrap();
+        assert_eq!(date, Date::new(2024, 3, 15).unwrap());
+        assert_eq!(date.serialize_to_string(), "2024,3,15");
+
+        assert!(Date::deserialize_complete("2024,2,29").is_ok());
+        assert!(Date::deserialize_complete("2023,2,29").is_err());
+        assert!(Date::deserialize_complete("2024,13,1").is_err());
+        assert!(Date::deserialize_complete("2024,4,31").is_err());
+        assert!(Date::deserialize_complete("2024,0,1").is_err());
+        assert!(Date::deserialize_complete("2024,3").is_err());
+    }
+
+    #[test]
+    fn time() {
+        let time = Time::deserialize_complete("13,45,2").unwrap();
+        assert_eq!(time, Time::new(13, 45, 2).unwrap());
+        assert_eq!(time.serialize_to_string(), "13,45,2");
+
+        assert!(Time::deserialize_complete("0,0,0").is_ok());
+        assert!(Time::deserialize_complete("24,0,0").is_err());
+        assert!(Time::deserialize_complete("12,60,0").is_err());
+        assert!(Time::deserialize_complete("12,0,60").is_err());
+    }
+
+    #[test]
+    fn date_time() {
+        let expected = DateTime {
+            date: Date::new(2024, 3, 15).unwrap(),
+            time: Time::new(13, 45, 2).unwrap(),
+        };
+        assert_eq!(
+            DateTime::deserialize_complete("2024,3,15,13,45,2").unwrap(),
+            expected
+        );
+        assert_eq!(
+            DateTime::deserialize_complete("2024-03-15T13:45:02").unwrap(),
+            expected
+        );
+        assert_eq!(
+            DateTime::deserialize_complete("2024-03-15 13:45:02").unwrap(),
+            expected
+        );
+        assert_eq!(expected.serialize_to_string(), "2024,3,15,13,45,2");
+
+        assert!(DateTime::deserialize_complete("2024-03-15X13:45:02").is_err());
+        assert!(DateTime::deserialize_complete("2024-02-30T13:45:02").is_err());
+    }
+}
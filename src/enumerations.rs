@@ -1,30 +1,148 @@
+/// Define an enum mapping each variant to a literal.
+///
+/// Variants may carry a payload, e.g. `Custom(u32) => "CUST"`, which is
+/// (de)serialized directly following the literal, e.g. `CUST12`.
+/// Variants are matched in order of declaration. A literal without payload must end
+/// at the end of input, a `,`, `;`, whitespace or a closing `"`, so e.g. `ON` does not
+/// match the beginning of `ONCE` or `ON2`.
+///
+/// A literal prefixed with `keyword`, e.g. `Voltage => keyword "VOLTage"`, is a keyword
+/// in SCPI notation, accepting any abbreviation from `VOLT` to `VOLTAGE`
+/// (see [`match_keyword`](crate::match_keyword)) and serialized in its long form.
+/// All other literals are matched and serialized exactly, including their case, e.g. `"kHz"`.
+///
+/// A final catch-all `_ => Unknown(String)` adds a variant capturing any
+/// unmatched token, up to the next `,`, `;` or whitespace, instead of failing.
+/// This keeps drivers working when a firmware update introduces new values.
+/// The captured token is serialized verbatim.
+///
+/// Since variants are matched in order, a variant matching a form of a later one shadows it,
+/// e.g. the keyword `TRIGger` before `TRIGgered`, whose short form `TRIG` it accepts,
+/// or a literal with payload matching the beginning of a later literal.
+/// The generated `validate()` detects this, as well as keywords without uppercase short form,
+/// and is meant to be called from a unit test of each enum.
+///
+/// Some instruments return enumerated values as quoted labels, e.g. `"TRIGGERED"`.
+/// Prefixing the enum with `quoted` wraps every literal in double quotes, both when
+/// matching and when serializing. Keywords remain abbreviable inside the quotes.
+///
+/// ```
+/// scpi_client::scpi_enum!(
+///     quoted pub enum State {
+///         Idle => "IDLE",
+///         Triggered => keyword "TRIGgered",
+///     }
+/// );
+/// ```
 #[macro_export]
 macro_rules! scpi_enum {
+    (quoted $($rest:tt)*) => {
+        $crate::scpi_enum!(@define true, $($rest)*);
+    };
+
+    (@token $quoted:literal, [$($payload:ty)?], keyword $literal:literal) => {
+        $crate::enumerations::Token {
+            literal: $literal,
+            keyword: true,
+            quoted: $quoted,
+            payload: $crate::scpi_enum!(@payload $($payload)?),
+        }
+    };
+
+    (@token $quoted:literal, [$($payload:ty)?], $literal:literal) => {
+        $crate::enumerations::Token {
+            literal: $literal,
+            keyword: false,
+            quoted: $quoted,
+            payload: $crate::scpi_enum!(@payload $($payload)?),
+        }
+    };
+
+    (@payload) => {
+        false
+    };
+
+    (@payload $payload:ty) => {
+        true
+    };
+
+    (@deserialize $input:ident, $variant:ident, $token:expr) => {
+        if let Ok(()) = $token.match_input($input) {
+            return Ok(Self::$variant);
+        }
+    };
+
+    (@deserialize $input:ident, $variant:ident ($payload:ty), $token:expr) => {
+        let mut cursor = *$input;
+        if let Ok(()) = $token.match_input(&mut cursor) {
+            if let Ok(payload) = <$payload as $crate::ScpiDeserialize>::deserialize(&mut cursor) {
+                *$input = cursor;
+                return Ok(Self::$variant(payload));
+            }
+        }
+    };
+
+    (@fallback $input:ident, $name:ident) => {
+        Err($crate::Error::ResponseDecoding(format!("Unexpected token for {}: `{}`", stringify!($name), $input)))
+    };
+
+    (@fallback $input:ident, $name:ident, $catch_all:ident) => {
+        $crate::enumerations::read_token($input).map(Self::$catch_all)
+    };
+
+    (@binding $binding:ident, $payload:ty) => {
+        $binding
+    };
+
+    (@serialize $out:ident, $binding:ident, $payload:ty) => {
+        $crate::ScpiSerialize::serialize($binding, $out);
+    };
+
+    (@len $binding:ident, $payload:ty) => {
+        $crate::ScpiSerialize::serialized_len($binding)
+    };
+
     (
+        @define $quoted:literal,
         $(#[$enum_meta:meta])*
         $vis:vis enum $name:ident {
             $(
                 $(#[$variant_meta:meta])*
-                $variant:ident => $literal:expr
-            ),* $(,)?
+                $variant:ident $(($payload:ty))? => $($keyword:ident)? $literal:literal
+            ),*
+            $(, _ => $catch_all:ident(String))? $(,)?
         }
     ) => {
         $(#[$enum_meta])*
         $vis enum $name {
             $(
                 $(#[$variant_meta])*
-                $variant,
+                $variant $(($payload))?,
             )*
+            $($catch_all(String),)?
+        }
+
+        impl $name {
+            /// Fail if a literal shadows a later one, see [`scpi_enum!`](crate::scpi_enum).
+            #[allow(dead_code)]
+            $vis fn validate() -> $crate::Result<()> {
+                $crate::enumerations::validate_literals(
+                    stringify!($name),
+                    &[$($crate::scpi_enum!(@token $quoted, [$($payload)?], $($keyword)? $literal)),*],
+                )
+            }
         }
 
         impl $crate::ScpiDeserialize for $name {
             fn deserialize(input: &mut &str) -> $crate::Result<Self> {
                 $(
-                    if let Ok(()) = $crate::match_literal(input, $literal) {
-                        return Ok(Self::$variant);
-                    }
+                    $crate::scpi_enum!(
+                        @deserialize input,
+                        $variant $(($payload))?,
+                        $crate::scpi_enum!(@token $quoted, [$($payload)?], $($keyword)? $literal)
+                    );
                 )*
-                Err($crate::Error::ResponseDecoding(format!("Unexpected token for {}: `{}`", stringify!($name), input)))
+                $crate::scpi_enum!(@fallback input, $name $(, $catch_all)?)
             }
         }
 
@@ -32,16 +150,233 @@ macro_rules! scpi_enum {
             fn serialize(&self, out: &mut String) {
                 match self {
                     $(
-                        Self::$variant => out.push_str($literal),
+                        Self::$variant $(($crate::scpi_enum!(@binding payload, $payload)))? => {
+                            $crate::scpi_enum!(@token $quoted, [$($payload)?], $($keyword)? $literal).serialize(out);
+                            $($crate::scpi_enum!(@serialize out, payload, $payload);)?
+                        }
+                    )*
+                    $(Self::$catch_all(token) => out.push_str(token),)?
+                }
+            }
+
+            fn serialized_len(&self) -> usize {
+                match self {
+                    $(
+                        Self::$variant $(($crate::scpi_enum!(@binding payload, $payload)))? => {
+                            $crate::scpi_enum!(@token $quoted, [$($payload)?], $($keyword)? $literal).len()
+                                $(+ $crate::scpi_enum!(@len payload, $payload))?
+                        }
                     )*
+                    $(Self::$catch_all(token) => token.len(),)?
+                }
+            }
+        }
+    };
+
+    ($($rest:tt)*) => {
+        $crate::scpi_enum!(@define false, $($rest)*);
+    };
+}
+
+/// Define an enum mapping each variant to an integer code, for instruments
+/// representing states as numbers, e.g. `0` for off and `2` for auto.
+///
+/// The code is (de)serialized as `i32`, an unknown code fails listing the valid ones.
+///
+/// ```
+/// scpi_client::scpi_enum_numeric!(
+///     pub enum AutoMode {
+///         Off => 0,
+///         On => 1,
+///         Auto => 2,
+///     }
+/// );
+/// ```
+#[macro_export]
+macro_rules! scpi_enum_numeric {
+    (
+        $(#[$enum_meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident => $code:literal
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$enum_meta])*
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )*
+        }
+
+        impl $name {
+            /// The integer code of the variant.
+            $vis fn code(&self) -> i32 {
+                match self {
+                    $(Self::$variant => $code,)*
                 }
             }
         }
+
+        impl $crate::ScpiDeserialize for $name {
+            fn deserialize(input: &mut &str) -> $crate::Result<Self> {
+                let mut cursor = *input;
+                let code = <i32 as $crate::ScpiDeserialize>::deserialize(&mut cursor)?;
+                let value = match code {
+                    $($code => Self::$variant,)*
+                    _ => {
+                        let codes: &[i32] = &[$($code),*];
+                        return Err($crate::Error::ResponseDecoding(format!(
+                            "Unexpected code for {}: `{code}`, expected one of {codes:?}",
+                            stringify!($name)
+                        )));
+                    }
+                };
+                *input = cursor;
+                Ok(value)
+            }
+        }
+
+        impl $crate::ScpiSerialize for $name {
+            fn serialize(&self, out: &mut String) {
+                $crate::ScpiSerialize::serialize(&self.code(), out);
+            }
+
+            fn serialized_len(&self) -> usize {
+                $crate::ScpiSerialize::serialized_len(&self.code())
+            }
+        }
     };
 }
 
+#[doc(hidden)]
+pub fn validate_literals(name: &str, tokens: &[Token]) -> crate::Result<()> {
+    for (index, later) in tokens.iter().enumerate() {
+        if later.keyword && later.short_form().is_empty() {
+            return Err(crate::Error::InvalidParameter(format!(
+                "{name}: keyword `{}` has no uppercase short form",
+                later.literal
+            )));
+        }
+        for earlier in &tokens[..index] {
+            let shadowed = later
+                .forms()
+                .iter()
+                .any(|form| earlier.match_input(&mut form.as_str()).is_ok());
+            if shadowed {
+                return Err(crate::Error::InvalidParameter(format!(
+                    "{name}: `{}` shadows the later `{}`, declare the longer one first",
+                    earlier.literal, later.literal
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Literal of an enum variant, see [`scpi_enum!`](crate::scpi_enum).
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub literal: &'static str,
+    pub keyword: bool,
+    pub quoted: bool,
+    /// A payload follows the literal directly, otherwise the token must end after it.
+    pub payload: bool,
+}
+
+impl Token {
+    pub fn match_input(&self, input: &mut &str) -> crate::Result<()> {
+        let mut cursor = *input;
+        if self.quoted {
+            crate::match_literal(&mut cursor, "\"")?;
+        }
+        if self.keyword {
+            crate::match_keyword(&mut cursor, self.literal)?;
+        } else {
+            crate::match_literal(&mut cursor, self.literal)?;
+        }
+        if self.quoted {
+            crate::match_literal(&mut cursor, "\"")?;
+        }
+        if !self.payload && !cursor.chars().next().is_none_or(is_token_end) {
+            return Err(crate::Error::ResponseDecoding(format!(
+                "Expected end of token after `{}`, not matched `{cursor}`",
+                self.literal
+            )));
+        }
+        *input = cursor;
+        Ok(())
+    }
+
+    pub fn serialize(&self, out: &mut String) {
+        if self.quoted {
+            out.push('"');
+        }
+        if self.keyword {
+            out.extend(self.literal.chars().map(|c| c.to_ascii_uppercase()));
+        } else {
+            out.push_str(self.literal);
+        }
+        if self.quoted {
+            out.push('"');
+        }
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.literal.len() + if self.quoted { 2 } else { 0 }
+    }
+
+    fn short_form(&self) -> &'static str {
+        let required = self
+            .literal
+            .find(|c: char| c.is_ascii_lowercase())
+            .unwrap_or(self.literal.len());
+        &self.literal[..required]
+    }
+
+    /// Forms of the token an instrument may send, as serialized.
+    fn forms(&self) -> Vec<String> {
+        let mut long = String::new();
+        self.serialize(&mut long);
+        if !self.keyword {
+            return vec![long];
+        }
+        let short = if self.quoted {
+            format!("\"{}\"", self.short_form())
+        } else {
+            self.short_form().to_string()
+        };
+        vec![long, short]
+    }
+}
+
+/// Whether `c` ends a token following an enum literal.
+fn is_token_end(c: char) -> bool {
+    matches!(c, ',' | ';' | '"') || c.is_whitespace()
+}
+
+#[doc(hidden)]
+pub fn read_token(input: &mut &str) -> crate::Result<String> {
+    let len = input
+        .find(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .unwrap_or(input.len());
+    if len == 0 {
+        return Err(crate::Error::ResponseDecoding(format!(
+            "Expected token, not matched `{input}`"
+        )));
+    }
+    let token = input[..len].to_string();
+    *input = &input[len..];
+    Ok(token)
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::wrappers::SkipWhitespace;
     use crate::{ScpiDeserialize, ScpiSerialize};
 
     scpi_enum!(
@@ -62,4 +397,298 @@ mod tests {
     fn deserialize() {
         assert_eq!(Color::deserialize_complete("RED").unwrap(), Color::Red);
     }
+
+    scpi_enum!(
+        #[derive(Debug, PartialEq)]
+        enum Mode {
+            Off => "OFF",
+            Custom(u32) => "CUST",
+            Level(f32) => "LEV ",
+            Auto => "AUTO",
+        }
+    );
+
+    #[test]
+    fn serialize_payload() {
+        assert_eq!(Mode::Off.serialize_to_string(), "OFF");
+        assert_eq!(Mode::Custom(12).serialize_to_string(), "CUST12");
+        assert_eq!(Mode::Level(-1.5).serialize_to_string(), "LEV -1.5");
+        assert_eq!(Mode::Auto.serialize_to_string(), "AUTO");
+        for mode in [Mode::Off, Mode::Custom(12), Mode::Level(-1.5), Mode::Auto] {
+            assert_eq!(mode.serialized_len(), mode.serialize_to_string().len());
+        }
+    }
+
+    #[test]
+    fn deserialize_payload() {
+        assert_eq!(Mode::deserialize_complete("OFF").unwrap(), Mode::Off);
+        assert_eq!(
+            Mode::deserialize_complete("CUST12").unwrap(),
+            Mode::Custom(12)
+        );
+        assert_eq!(
+            Mode::deserialize_complete("LEV -1.5").unwrap(),
+            Mode::Level(-1.5)
+        );
+        assert_eq!(Mode::deserialize_complete("AUTO").unwrap(), Mode::Auto);
+        assert!(Mode::deserialize_complete("CUST").is_err());
+        assert!(Mode::deserialize_complete("LEV").is_err());
+
+        let input = &mut "CUSTX";
+        assert!(Mode::deserialize(input).is_err());
+        assert_eq!(*input, "CUSTX");
+    }
+
+    scpi_enum_numeric!(
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum Coupling {
+            Ground => -1,
+            Dc => 0,
+            Ac => 1,
+            Auto => 2,
+        }
+    );
+
+    #[test]
+    fn numeric() {
+        assert_eq!(Coupling::Ground.serialize_to_string(), "-1");
+        assert_eq!(Coupling::Auto.serialize_to_string(), "2");
+        assert_eq!(Coupling::Ground.serialized_len(), 2);
+        assert_eq!(Coupling::Ac.code(), 1);
+        for coupling in [Coupling::Ground, Coupling::Dc, Coupling::Ac, Coupling::Auto] {
+            crate::assert_scpi_roundtrip!(coupling);
+        }
+        assert_eq!(Coupling::deserialize_complete("+1").unwrap(), Coupling::Ac);
+
+        let input = &mut "5";
+        let error = Coupling::deserialize(input).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Received data does not match expected format: \
+             Unexpected code for Coupling: `5`, expected one of [-1, 0, 1, 2]"
+        );
+        assert_eq!(*input, "5");
+        assert!(Coupling::deserialize_complete("AC").is_err());
+    }
+
+    scpi_enum!(
+        #[derive(Debug, PartialEq, Eq)]
+        enum Repeat {
+            On => "ON",
+            Once => "ONCE",
+        }
+    );
+
+    scpi_enum!(
+        enum Shadowed {
+            Trigger => keyword "TRIGger",
+            Triggered => keyword "TRIGgered",
+        }
+    );
+
+    scpi_enum!(
+        enum ShadowedPayload {
+            Channel(u8) => "CH",
+            Chassis => "CHASSIS",
+        }
+    );
+
+    scpi_enum!(
+        enum ShadowedKeyword {
+            Voltage => keyword "VOLTage",
+            VoltageAc => keyword "VOLTage:AC",
+        }
+    );
+
+    #[test]
+    fn validate() {
+        Color::validate().unwrap();
+        Mode::validate().unwrap();
+        Function::validate().unwrap();
+        Trigger::validate().unwrap();
+        Label::validate().unwrap();
+        Repeat::validate().unwrap();
+        assert_eq!(
+            Shadowed::validate().unwrap_err().to_string(),
+            "Invalid parameter: Shadowed: `TRIGger` shadows the later `TRIGgered`, declare the longer one first"
+        );
+        assert!(ShadowedKeyword::validate().is_err());
+        assert!(ShadowedPayload::validate().is_err());
+    }
+
+    scpi_enum!(
+        #[derive(Debug, PartialEq, Eq)]
+        enum Lower {
+            Auto => "auto",
+            Off => "off",
+        }
+    );
+
+    scpi_enum!(
+        #[derive(Debug, PartialEq, Eq)]
+        enum FrequencyUnit {
+            Kilohertz => "kHz",
+            Megahertz => "MHz",
+        }
+    );
+
+    scpi_enum!(
+        enum LowerKeyword {
+            Auto => keyword "auto",
+        }
+    );
+
+    #[test]
+    fn exact_literals() {
+        assert_eq!(Lower::deserialize_complete("off").unwrap(), Lower::Off);
+        assert_eq!(Lower::deserialize_complete("auto").unwrap(), Lower::Auto);
+        assert!(Lower::deserialize_complete("OFF").is_err());
+        assert_eq!(Lower::Off.serialize_to_string(), "off");
+        Lower::validate().unwrap();
+
+        assert_eq!(FrequencyUnit::Kilohertz.serialize_to_string(), "kHz");
+        assert_eq!(
+            FrequencyUnit::deserialize_complete("MHz").unwrap(),
+            FrequencyUnit::Megahertz
+        );
+        assert!(FrequencyUnit::deserialize_complete("KHZ").is_err());
+        FrequencyUnit::validate().unwrap();
+
+        assert_eq!(
+            LowerKeyword::validate().unwrap_err().to_string(),
+            "Invalid parameter: LowerKeyword: keyword `auto` has no uppercase short form"
+        );
+        assert!(LowerKeyword::deserialize_complete("auto").is_err());
+    }
+
+    #[test]
+    fn deserialize_skip_whitespace() {
+        assert!(Color::deserialize_complete(" RED").is_err());
+        assert_eq!(
+            SkipWhitespace::<Color>::deserialize_complete(" RED").unwrap(),
+            SkipWhitespace(Color::Red)
+        );
+    }
+
+    scpi_enum!(
+        #[derive(Debug, PartialEq, Eq)]
+        enum Function {
+            Voltage => keyword "VOLTage",
+            Current => keyword "CURRent",
+            Resistance => "RES",
+        }
+    );
+
+    #[test]
+    fn keyword() {
+        assert_eq!(Function::Voltage.serialize_to_string(), "VOLTAGE");
+        assert_eq!(Function::Resistance.serialize_to_string(), "RES");
+        for response in ["VOLT", "VOLTAG", "VOLTAGE", "volt"] {
+            assert_eq!(
+                Function::deserialize_complete(response).unwrap(),
+                Function::Voltage
+            );
+        }
+        assert_eq!(
+            Function::deserialize_complete("CURR").unwrap(),
+            Function::Current
+        );
+        assert!(Function::deserialize_complete("VOL").is_err());
+        assert!(Function::deserialize_complete("res").is_err());
+    }
+
+    scpi_enum!(
+        #[derive(Debug, PartialEq, Eq)]
+        enum Trigger {
+            Immediate => "IMM",
+            Bus => "BUS",
+            _ => Unknown(String),
+        }
+    );
+
+    #[test]
+    fn catch_all() {
+        assert_eq!(Trigger::deserialize_complete("BUS").unwrap(), Trigger::Bus);
+        assert_eq!(
+            Trigger::deserialize_complete("LAN3").unwrap(),
+            Trigger::Unknown("LAN3".to_string())
+        );
+        assert_eq!(
+            Trigger::Unknown("LAN3".to_string()).serialize_to_string(),
+            "LAN3"
+        );
+
+        let input = &mut "EXT,1";
+        assert_eq!(
+            Trigger::deserialize(input).unwrap(),
+            Trigger::Unknown("EXT".to_string())
+        );
+        assert_eq!(*input, ",1");
+        assert_eq!(
+            Trigger::deserialize_complete("BUSY").unwrap(),
+            Trigger::Unknown("BUSY".to_string())
+        );
+        let input = &mut "BUS2,1";
+        assert_eq!(
+            Trigger::deserialize(input).unwrap(),
+            Trigger::Unknown("BUS2".to_string())
+        );
+        assert_eq!(*input, ",1");
+        assert!(Trigger::deserialize_complete("").is_err());
+        assert!(Color::deserialize_complete("PINK").is_err());
+    }
+
+    scpi_enum!(
+        quoted
+        #[derive(Debug, PartialEq, Eq)]
+        enum Label {
+            Idle => "IDLE",
+            Triggered => keyword "TRIGgered",
+        }
+    );
+
+    #[test]
+    fn quoted() {
+        assert_eq!(Label::Idle.serialize_to_string(), "\"IDLE\"");
+        assert_eq!(Label::Triggered.serialize_to_string(), "\"TRIGGERED\"");
+        assert_eq!(Label::Triggered.serialized_len(), 11);
+        for response in ["\"TRIG\"", "\"triggered\""] {
+            assert_eq!(
+                Label::deserialize_complete(response).unwrap(),
+                Label::Triggered
+            );
+        }
+        crate::assert_scpi_roundtrip!(Label::Idle);
+        assert!(Label::deserialize_complete("IDLE").is_err());
+        assert!(Label::deserialize_complete("\"IDLE").is_err());
+
+        let input = &mut "\"TRIGX\"";
+        assert!(Label::deserialize(input).is_err());
+        assert_eq!(*input, "\"TRIGX\"");
+    }
+
+    #[test]
+    fn composition() {
+        let input = &mut "BLAU;RED";
+        assert_eq!(Color::deserialize(input).unwrap(), Color::Blue);
+        assert!(crate::match_literal(input, ";").is_ok());
+        assert_eq!(Color::deserialize(input).unwrap(), Color::Red);
+        assert!(input.is_empty());
+
+        let input = &mut "LEV 1.5,AUTO";
+        assert_eq!(Mode::deserialize(input).unwrap(), Mode::Level(1.5));
+        assert_eq!(*input, ",AUTO");
+
+        let input = &mut "ON;ONCE;ON";
+        assert_eq!(Repeat::deserialize(input).unwrap(), Repeat::On);
+        assert!(crate::match_literal(input, ";").is_ok());
+        assert_eq!(Repeat::deserialize(input).unwrap(), Repeat::Once);
+        assert!(crate::match_literal(input, ";").is_ok());
+        assert_eq!(Repeat::deserialize(input).unwrap(), Repeat::On);
+        assert!(input.is_empty());
+
+        let input = &mut "ONX;ON";
+        assert!(Repeat::deserialize(input).is_err());
+        assert_eq!(*input, "ONX;ON");
+    }
 }
@@ -0,0 +1,86 @@
+use crate::ScpiSerialize;
+
+/// Numeric suffix of a header node, e.g. the `2` of `OUTPut2:STATe?`.
+///
+/// Used as converter of a channel field, e.g.
+/// `impl_scpi_serialize!(OutputState, ["OUTP", channel as NumericSuffix, ":STAT?"])`.
+/// With `OMIT_ONE`, the suffix `1` is omitted, as SCPI defines it as the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NumericSuffix<const OMIT_ONE: bool = false>(pub u32);
+
+impl<const OMIT_ONE: bool> From<u8> for NumericSuffix<OMIT_ONE> {
+    fn from(value: u8) -> Self {
+        Self(value.into())
+    }
+}
+
+impl<const OMIT_ONE: bool> From<u16> for NumericSuffix<OMIT_ONE> {
+    fn from(value: u16) -> Self {
+        Self(value.into())
+    }
+}
+
+impl<const OMIT_ONE: bool> From<u32> for NumericSuffix<OMIT_ONE> {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl<const OMIT_ONE: bool> ScpiSerialize for NumericSuffix<OMIT_ONE> {
+    fn serialize(&self, out: &mut String) {
+        if !(OMIT_ONE && self.0 == 1) {
+            self.0.serialize(out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::impl_scpi_serialize;
+
+    struct OutputStateQuery {
+        channel: u8,
+    }
+    impl_scpi_serialize!(
+        OutputStateQuery,
+        ["OUTP", channel as NumericSuffix, ":STAT?"]
+    );
+
+    struct Voltage {
+        channel: u8,
+        volts: f32,
+    }
+    impl_scpi_serialize!(
+        Voltage,
+        ["SOUR", channel as NumericSuffix<true>, ":VOLT ", volts]
+    );
+
+    #[test]
+    fn suffix() {
+        assert_eq!(
+            OutputStateQuery { channel: 2 }.serialize_to_string(),
+            "OUTP2:STAT?"
+        );
+        assert_eq!(
+            OutputStateQuery { channel: 1 }.serialize_to_string(),
+            "OUTP1:STAT?"
+        );
+        assert_eq!(
+            Voltage {
+                channel: 1,
+                volts: 1.5
+            }
+            .serialize_to_string(),
+            "SOUR:VOLT 1.5"
+        );
+        assert_eq!(
+            Voltage {
+                channel: 3,
+                volts: 1.5
+            }
+            .serialize_to_string(),
+            "SOUR3:VOLT 1.5"
+        );
+    }
+}
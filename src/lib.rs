@@ -1,8 +1,23 @@
 use regex::Regex;
 use thiserror::Error;
 
+pub mod batch;
+pub mod block;
+pub mod commands;
+pub mod complex;
+pub mod containers;
+pub mod datetime;
 pub mod enumerations;
+pub mod headers;
+pub mod measurement;
+pub mod numeric;
 pub mod primitives;
+pub mod status;
+pub mod strings;
+pub mod trace;
+pub mod transport;
+pub mod units;
+pub mod wrappers;
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -10,6 +25,24 @@ pub enum Error {
     //  and unexpected symbols
     #[error("Received data does not match expected format: {0}")]
     ResponseDecoding(String),
+    #[error("Value out of range: {0}")]
+    OutOfRange(String),
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("No response within {after:?}")]
+    Timeout { after: std::time::Duration },
+    #[error("Transport error: {0}")]
+    Transport(String),
+    #[error("VISA error: {0}")]
+    Visa(String),
+    #[error("Instrument sent an empty response")]
+    EmptyResponse,
+    #[error("Response exceeds {limit} bytes")]
+    ResponseTooLarge { limit: usize },
+    #[error("Instrument reported errors: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    Instrument(Vec<commands::ErrorEntry>),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -17,6 +50,15 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub trait ScpiSerialize {
     fn serialize(&self, out: &mut String);
 
+    /// Length of the serialized form in bytes, e.g. to pre-size buffers.
+    /// The default serializes into a temporary string, implementations
+    /// override it if the length is cheaper to compute.
+    fn serialized_len(&self) -> usize {
+        let mut out = String::new();
+        self.serialize(&mut out);
+        out.len()
+    }
+
     fn serialize_to_string(&self) -> String {
         let mut out = String::new();
         self.serialize(&mut out);
@@ -33,11 +75,28 @@ where
 
     fn deserialize(input: &mut &str) -> Result<Self>;
 
+    /// Deserialize a value spanning the complete input. Trailing input fails with
+    /// [`Error::ResponseDecoding`], rather than panicking, so a response of unexpected
+    /// shape can be handled by the caller like any other decoding error.
     fn deserialize_complete(mut input: &str) -> Result<Self> {
         let result = Self::deserialize(&mut input)?;
-        check_empty(input).unwrap();
+        check_empty(input)?;
+        Ok(result)
+    }
+
+    /// Lenient variant of [`ScpiDeserialize::deserialize_complete`],
+    /// trailing ASCII whitespace (e.g. a leftover `\r\n`) is ignored.
+    fn deserialize_complete_trimmed(mut input: &str) -> Result<Self> {
+        let result = Self::deserialize(&mut input)?;
+        check_empty(input.trim_end_matches(|c: char| c.is_ascii_whitespace()))?;
         Ok(result)
     }
+
+    /// Deserialize one value and return it together with the unparsed rest of the input.
+    fn deserialize_remaining(mut input: &str) -> Result<(Self, &str)> {
+        let result = Self::deserialize(&mut input)?;
+        Ok((result, input))
+    }
 }
 
 pub trait ScpiRequest: ScpiSerialize {
@@ -47,13 +106,57 @@ pub trait ScpiRequest: ScpiSerialize {
     type Response;
 }
 
+/// Wire form of a request and the name of its response type, for snapshot tests
+/// asserting that the commands of a driver do not drift, e.g. `("*RST", "EmptyResponse")`.
+///
+/// Module paths are stripped from the type name, e.g. `Vec<f64>` instead of
+/// `alloc::vec::Vec<f64>`, the exact format of the name is not guaranteed across
+/// compiler versions.
+pub trait Snapshot {
+    fn snapshot(&self) -> (String, String);
+}
+
+impl<R: ScpiRequest + ?Sized> Snapshot for R {
+    fn snapshot(&self) -> (String, String) {
+        lazy_static::lazy_static! {
+            static ref MODULE_PATH: Regex = Regex::new(r"\b[a-z_][a-z0-9_]*::").unwrap();
+        }
+        let name = std::any::type_name::<R::Response>();
+        (
+            self.serialize_to_string(),
+            MODULE_PATH.replace_all(name, "").into_owned(),
+        )
+    }
+}
+
+impl<T: ScpiSerialize + ?Sized> ScpiSerialize for &T {
+    fn serialize(&self, out: &mut String) {
+        (**self).serialize(out);
+    }
+
+    fn serialized_len(&self) -> usize {
+        (**self).serialized_len()
+    }
+}
+
 // TODO remove? is thits truly universal?
+/// Serialization is write-only: `None` emits nothing, which is convenient
+/// for omitted trailing parameters, but cannot be deserialized again.
+/// Use [`wrappers::WithPlaceholder`] for an explicit, round-trippable token.
+///
+/// Separators around the value are still emitted, so `None` in the middle of a command
+/// leaves an empty field and a trailing `None` a dangling separator.
+/// Use [`wrappers::OptionalTrailing`] for optional trailing parameters.
 impl<T: ScpiSerialize> ScpiSerialize for Option<T> {
     fn serialize(&self, out: &mut String) {
         if let Some(inner) = self {
             inner.serialize(out);
         }
     }
+
+    fn serialized_len(&self) -> usize {
+        self.as_ref().map_or(0, ScpiSerialize::serialized_len)
+    }
 }
 
 /// Response type to indicate that no answer is expected.
@@ -67,12 +170,29 @@ macro_rules! impl_scpi_serialize {
         impl $crate::ScpiSerialize for $type {
             fn serialize(&self, out: &mut String) {
                 $(
-                    impl_scpi_serialize!(@part self, out, $part $(as $converter)*);
+                    $crate::impl_scpi_serialize!(@part self, out, $part $(as $converter)*);
                 )*
             }
+
+            fn serialized_len(&self) -> usize {
+                0 $(+ $crate::impl_scpi_serialize!(@len self, $part $(as $converter)*))*
+            }
         }
     };
 
+    (@len $self:ident, $lit:literal) => {
+        $lit.len()
+    };
+
+    (@len $self:ident, $field:ident) => {
+        $crate::ScpiSerialize::serialized_len(&$self.$field)
+    };
+
+    (@len $self:ident, $field:ident as $converter:ty) => {{
+        let convert: $converter = $self.$field.into();
+        $crate::ScpiSerialize::serialized_len(&convert)
+    }};
+
     // Handle string literals
     (@part $self:ident, $out:ident, $lit:literal) => {
         $out.push_str($lit);
@@ -99,6 +219,114 @@ macro_rules! impl_scpi_request {
     };
 }
 
+/// Define a set command and the corresponding query for one header.
+///
+/// `scpi_command_pair!(pub struct Voltage, VoltageQuery => "SOURce:VOLTage", f32);`
+/// defines `Voltage { value }` serializing to `SOURce:VOLTage <value>`
+/// and `VoltageQuery` serializing to `SOURce:VOLTage?` with an `f32` response.
+/// The header may be any constant `&str` expression, e.g. a `concat!`.
+#[macro_export]
+macro_rules! scpi_command_pair {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $set:ident, $query:ident => $header:expr, $value:ty $(,)?
+    ) => {
+        $(#[$meta])*
+        $vis struct $set {
+            pub value: $value,
+        }
+        impl $crate::ScpiSerialize for $set {
+            fn serialize(&self, out: &mut String) {
+                out.push_str($header);
+                out.push(' ');
+                $crate::ScpiSerialize::serialize(&self.value, out);
+            }
+
+            fn serialized_len(&self) -> usize {
+                $header.len() + 1 + $crate::ScpiSerialize::serialized_len(&self.value)
+            }
+        }
+
+        $(#[$meta])*
+        $vis struct $query;
+        impl $crate::ScpiSerialize for $query {
+            fn serialize(&self, out: &mut String) {
+                out.push_str($header);
+                out.push('?');
+            }
+
+            fn serialized_len(&self) -> usize {
+                $header.len() + 1
+            }
+        }
+        $crate::impl_scpi_request!($query, $value);
+    };
+}
+
+/// Define several command pairs, see [`scpi_command_pair!`], below a common subsystem root.
+///
+/// ```
+/// scpi_client::scpi_subsystem!("SOURce:VOLTage" {
+///     pub struct Level, LevelQuery => ":LEVel", f32;
+///     pub struct Range, RangeQuery => ":RANGe", f32;
+/// });
+/// # use scpi_client::ScpiSerialize;
+/// assert_eq!(Level { value: 1.5 }.serialize_to_string(), "SOURce:VOLTage:LEVel 1.5");
+/// assert_eq!(RangeQuery.serialize_to_string(), "SOURce:VOLTage:RANGe?");
+/// ```
+#[macro_export]
+macro_rules! scpi_subsystem {
+    (
+        $root:literal {
+            $(
+                $(#[$meta:meta])*
+                $vis:vis struct $set:ident, $query:ident => $leaf:literal, $value:ty;
+            )*
+        }
+    ) => {
+        $(
+            $crate::scpi_command_pair!(
+                $(#[$meta])*
+                $vis struct $set, $query => concat!($root, $leaf), $value
+            );
+        )*
+    };
+}
+
+/// Assert that serializing and deserializing a value yields the value again.
+///
+/// Values unequal to themselves (NaN) are considered equal to each other.
+#[macro_export]
+macro_rules! assert_scpi_roundtrip {
+    ($value:expr) => {{
+        let value = $value;
+        let serialized = $crate::ScpiSerialize::serialize_to_string(&value);
+        let deserialized = $crate::ScpiDeserialize::deserialize_complete(&serialized)
+            .unwrap_or_else(|e| panic!("Failed to deserialize `{serialized}`: {e}"));
+        assert!(
+            $crate::roundtrip_eq(&value, &deserialized),
+            "Round trip of `{serialized}` yields a different value"
+        );
+    }};
+}
+
+#[doc(hidden)]
+#[allow(clippy::eq_op)]
+pub fn roundtrip_eq<T: PartialEq>(original: &T, result: &T) -> bool {
+    original == result || (original != original && result != result)
+}
+
+/// Up to `len` bytes of the input without consuming them, shortened to the
+/// end of input or the last complete character.
+pub fn peek_prefix<'a>(input: &&'a str, len: usize) -> &'a str {
+    &input[..input.floor_char_boundary(len)]
+}
+
+/// Whether the input starts with `literal`, without consuming it.
+pub fn starts_with_literal(input: &str, literal: &str) -> bool {
+    input.starts_with(literal)
+}
+
 pub fn match_literal(input: &mut &str, literal: &'static str) -> Result<()> {
     if let Some(rest) = input.strip_prefix(literal) {
         *input = rest;
@@ -110,6 +338,58 @@ pub fn match_literal(input: &mut &str, literal: &'static str) -> Result<()> {
     }
 }
 
+/// Match a header regardless of the optional leading root specifier `:`,
+/// so `VOLT` and `:VOLT` both match input starting with either.
+pub fn match_header(input: &mut &str, header: &'static str) -> Result<()> {
+    let header = header.strip_prefix(':').unwrap_or(header);
+    let mut cursor = input.strip_prefix(':').unwrap_or(input);
+    match_literal(&mut cursor, header)?;
+    *input = cursor;
+    Ok(())
+}
+
+/// Match the first of several literals, tried in order, and return its index.
+pub fn match_one_of(input: &mut &str, literals: &[&'static str]) -> Result<usize> {
+    for (index, literal) in literals.iter().enumerate() {
+        if match_literal(input, literal).is_ok() {
+            return Ok(index);
+        }
+    }
+    Err(Error::ResponseDecoding(format!(
+        "Expected one of {literals:?}, not matched `{input}`"
+    )))
+}
+
+/// Match a keyword given in SCPI notation, e.g. `VOLTage`, where the uppercase part is
+/// required and the lowercase part optional. Any abbreviation between the short form `VOLT`
+/// and the long form `VOLTAGE` is accepted, compared case-insensitively.
+/// The match must not be followed by an alphanumeric character, so e.g. `VOLTS` is rejected
+/// instead of leaving `S`.
+pub fn match_keyword(input: &mut &str, keyword: &str) -> Result<()> {
+    let required = keyword
+        .find(|c: char| c.is_ascii_lowercase())
+        .unwrap_or(keyword.len());
+    if required == 0 {
+        return Err(Error::InvalidParameter(format!(
+            "Keyword `{keyword}` has no uppercase short form"
+        )));
+    }
+    let mut matched = 0;
+    for (expected, actual) in keyword.bytes().zip(input.bytes()) {
+        if !expected.eq_ignore_ascii_case(&actual) {
+            break;
+        }
+        matched += 1;
+    }
+    if matched < required || input[matched..].starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return Err(Error::ResponseDecoding(format!(
+            "Expected keyword `{keyword}` not matched `{input}`"
+        )));
+    }
+    *input = &input[matched..];
+    Ok(())
+}
+
 pub fn read_until<'a>(input: &mut &'a str, delimiter: char) -> Result<&'a str> {
     if let Some(index) = input.find(delimiter) {
         let (head, tail) = input.split_at(index);
@@ -122,6 +402,48 @@ pub fn read_until<'a>(input: &mut &'a str, delimiter: char) -> Result<&'a str> {
     }
 }
 
+/// Same as [`read_until`], but returns and consumes the complete remaining input
+/// if the delimiter is absent, e.g. for the last element of a list.
+pub fn read_until_or_end<'a>(input: &mut &'a str, delimiter: char) -> &'a str {
+    match input.find(delimiter) {
+        Some(index) => {
+            let (head, tail) = input.split_at(index);
+            *input = &tail[delimiter.len_utf8()..];
+            head
+        }
+        None => std::mem::take(input),
+    }
+}
+
+/// Read a line terminated by `\n` or the end of input,
+/// the terminator is consumed but not returned.
+pub fn read_line<'a>(input: &mut &'a str) -> Result<&'a str> {
+    if input.is_empty() {
+        return Err(Error::ResponseDecoding(
+            "Expected a line, but input is empty".to_string(),
+        ));
+    }
+
+    match input.find('\n') {
+        Some(index) => {
+            let (head, tail) = input.split_at(index);
+            *input = &tail[1..]; // from 1 to skip terminator
+            Ok(head)
+        }
+        None => {
+            let head = *input;
+            *input = "";
+            Ok(head)
+        }
+    }
+}
+
+/// Same as [`read_line`], but also strips a trailing `\r` of the line.
+pub fn read_line_crlf<'a>(input: &mut &'a str) -> Result<&'a str> {
+    let line = read_line(input)?;
+    Ok(line.strip_suffix('\r').unwrap_or(line))
+}
+
 pub fn read_prefix<'a>(input: &mut &'a str, pattern: &Regex) -> &'a str {
     let length = pattern.find(input).map_or(0, |m| m.end());
     let (head, tail) = input.split_at(length);
@@ -129,6 +451,14 @@ pub fn read_prefix<'a>(input: &mut &'a str, pattern: &Regex) -> &'a str {
     head
 }
 
+/// Consume the leading run of characters satisfying `predicate`, possibly empty.
+pub fn read_while<'a>(input: &mut &'a str, predicate: impl Fn(char) -> bool) -> &'a str {
+    let len = input.find(|c| !predicate(c)).unwrap_or(input.len());
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    head
+}
+
 pub fn read_exact<'a>(input: &mut &'a str, len: usize) -> Result<&'a str> {
     if input.len() < len {
         return Err(Error::ResponseDecoding(format!(
@@ -141,12 +471,47 @@ pub fn read_exact<'a>(input: &mut &'a str, len: usize) -> Result<&'a str> {
     Ok(head)
 }
 
+/// Read a payload of `n` bytes, e.g. with a length provided out-of-band.
+/// Unlike slicing, a count ending within a character is reported as error.
+pub fn read_n_bytes<'a>(input: &mut &'a str, n: usize) -> Result<&'a str> {
+    if input.len() < n || !input.is_char_boundary(n) {
+        return Err(Error::ResponseDecoding(format!(
+            "Failed to read {n} bytes from `{input}`"
+        )));
+    }
+    read_exact(input, n)
+}
+
+/// Read a payload of exactly `n` bytes, which must be followed by nothing but an optional
+/// `\n` or `\r\n` terminator. The complete input is consumed.
+pub fn read_remaining_after<'a>(input: &mut &'a str, n: usize) -> Result<&'a str> {
+    let mut cursor = *input;
+    let payload = read_n_bytes(&mut cursor, n)?;
+    let rest = cursor
+        .strip_suffix("\r\n")
+        .or_else(|| cursor.strip_suffix('\n'))
+        .unwrap_or(cursor);
+    if !rest.is_empty() {
+        return Err(Error::ResponseDecoding(format!(
+            "Expected {n} bytes, but {} more remain in `{input}`",
+            rest.len()
+        )));
+    }
+    *input = "";
+    Ok(payload)
+}
+
 pub fn read_all(input: &mut &str) -> Result<String> {
     let result = input.to_string();
     *input = "";
     Ok(result)
 }
 
+/// Advance past leading ASCII spaces and tabs.
+pub fn skip_whitespace(input: &mut &str) {
+    *input = input.trim_start_matches([' ', '\t']);
+}
+
 pub fn check_empty(input: &str) -> Result<()> {
     if input.is_empty() {
         Ok(())
@@ -161,6 +526,160 @@ pub fn check_empty(input: &str) -> Result<()> {
 mod tests {
     use super::*;
 
+    struct Rst;
+    impl_scpi_serialize!(Rst, ["*RST"]);
+    impl_scpi_request!(Rst, EmptyResponse);
+
+    struct CurveQuery {
+        channel: u8,
+    }
+    impl_scpi_serialize!(CurveQuery, ["CURV", channel, "?"]);
+    impl_scpi_request!(CurveQuery, containers::Separated<(Vec<f64>, String), ';'>);
+
+    #[test]
+    fn test_snapshot() {
+        assert_eq!(
+            Rst.snapshot(),
+            ("*RST".to_string(), "EmptyResponse".to_string())
+        );
+        assert_eq!(
+            CurveQuery { channel: 2 }.snapshot(),
+            (
+                "CURV2?".to_string(),
+                "Separated<(Vec<f64>, String), ';'>".to_string()
+            )
+        );
+        assert_eq!(
+            commands::SystemErrorQuery.snapshot().1,
+            "ErrorEntry".to_string()
+        );
+    }
+
+    #[test]
+    fn test_deserialize_remaining() {
+        let (value, rest) = u32::deserialize_remaining("12,34").unwrap();
+        assert_eq!(value, 12);
+        assert_eq!(rest, ",34");
+
+        let (value, rest) = u32::deserialize_remaining("12").unwrap();
+        assert_eq!(value, 12);
+        assert_eq!(rest, "");
+
+        assert!(u32::deserialize_remaining("x").is_err());
+    }
+
+    #[test]
+    fn test_deserialize_complete() {
+        assert_eq!(u32::deserialize_complete("12").unwrap(), 12);
+        assert!(u32::deserialize_complete("12\r\n").is_err());
+        assert!(u32::deserialize_complete("12,").is_err());
+    }
+
+    #[test]
+    fn test_deserialize_complete_trailing_input_is_error() {
+        let result = std::panic::catch_unwind(|| u32::deserialize_complete("12,34"));
+        let error = result.expect("trailing input must not panic").unwrap_err();
+        assert!(matches!(error, Error::ResponseDecoding(_)), "{error}");
+        assert!(error.to_string().contains("`,34`"), "{error}");
+    }
+
+    #[test]
+    fn test_deserialize_complete_trimmed() {
+        assert_eq!(u32::deserialize_complete_trimmed("12").unwrap(), 12);
+        assert_eq!(u32::deserialize_complete_trimmed("12\r\n").unwrap(), 12);
+        assert_eq!(u32::deserialize_complete_trimmed("12 \t").unwrap(), 12);
+        assert!(u32::deserialize_complete_trimmed("12 ,").is_err());
+    }
+
+    scpi_command_pair!(
+        #[derive(Debug, PartialEq)]
+        struct Voltage, VoltageQuery => "SOURce:VOLTage", f32
+    );
+
+    #[test]
+    fn test_command_pair() {
+        assert_eq!(
+            Voltage { value: 1.5 }.serialize_to_string(),
+            "SOURce:VOLTage 1.5"
+        );
+        assert_eq!(VoltageQuery.serialize_to_string(), "SOURce:VOLTage?");
+        assert_eq!(Voltage { value: 1.5 }.serialized_len(), 18);
+        assert_eq!(VoltageQuery.serialized_len(), 15);
+        let response: <VoltageQuery as ScpiRequest>::Response =
+            ScpiDeserialize::deserialize_complete("1.5").unwrap();
+        assert_eq!(response, 1.5);
+    }
+
+    scpi_subsystem!("SOURce:CURRent" {
+        #[derive(Debug, PartialEq)]
+        struct Level, LevelQuery => ":LEVel", f32;
+        struct Protection, ProtectionQuery => ":PROTection:STATe", primitives::Boolean;
+    });
+
+    #[test]
+    fn test_subsystem() {
+        assert_eq!(
+            Level { value: 0.25 }.serialize_to_string(),
+            "SOURce:CURRent:LEVel 0.25"
+        );
+        assert_eq!(LevelQuery.serialize_to_string(), "SOURce:CURRent:LEVel?");
+        assert_eq!(
+            Protection { value: true.into() }.serialize_to_string(),
+            "SOURce:CURRent:PROTection:STATe ON"
+        );
+        assert_eq!(
+            ProtectionQuery.serialize_to_string(),
+            "SOURce:CURRent:PROTection:STATe?"
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_eq() {
+        assert!(roundtrip_eq(&1.5, &1.5));
+        assert!(roundtrip_eq(&f64::NAN, &f64::NAN));
+        assert!(!roundtrip_eq(&f64::NAN, &1.5));
+        assert!(!roundtrip_eq(&1, &2));
+    }
+
+    #[derive(PartialEq)]
+    struct Truncated(u8);
+
+    impl ScpiSerialize for Truncated {
+        fn serialize(&self, out: &mut String) {
+            (self.0 / 10).serialize(out);
+        }
+    }
+
+    impl ScpiDeserialize for Truncated {
+        fn deserialize(input: &mut &str) -> Result<Self> {
+            u8::deserialize(input).map(Self)
+        }
+    }
+
+    #[test]
+    fn test_assert_roundtrip() {
+        assert_scpi_roundtrip!(Truncated(0));
+        assert_scpi_roundtrip!(vec![1.5f32, f32::MAX]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_roundtrip_mismatch() {
+        assert_scpi_roundtrip!(Truncated(12));
+    }
+
+    #[test]
+    fn test_error_source() {
+        fn read() -> Result<()> {
+            Err(std::io::Error::from(std::io::ErrorKind::BrokenPipe))?
+        }
+
+        let error = read().unwrap_err();
+        assert!(matches!(&error, Error::Io(e) if e.kind() == std::io::ErrorKind::BrokenPipe));
+        assert!(std::error::Error::source(&error).is_some());
+        assert!(std::error::Error::source(&Error::Transport("closed".to_string())).is_none());
+    }
+
     #[test]
     fn test_check_empty() {
         assert!(check_empty("").is_ok());
@@ -176,6 +695,46 @@ mod tests {
         assert!(check_empty(input).is_ok());
     }
 
+    #[test]
+    fn test_read_while() {
+        let input = &mut "123abc,4";
+        assert_eq!(read_while(input, |c| c.is_ascii_digit()), "123");
+        assert_eq!(read_while(input, |c| c.is_ascii_digit()), "");
+        assert_eq!(read_while(input, char::is_alphabetic), "abc");
+        assert_eq!(*input, ",4");
+
+        let input = &mut "\u{b5}s";
+        assert_eq!(read_while(input, char::is_alphabetic), "\u{b5}s");
+        assert!(input.is_empty());
+        assert_eq!(read_while(input, char::is_alphabetic), "");
+    }
+
+    #[test]
+    fn test_read_n_bytes() {
+        let input = &mut "ab\u{e4}c";
+        assert!(read_n_bytes(input, 3).is_err());
+        assert!(read_n_bytes(input, 6).is_err());
+        assert_eq!(*input, "ab\u{e4}c");
+        assert_eq!(read_n_bytes(input, 4).unwrap(), "ab\u{e4}");
+        assert_eq!(read_n_bytes(input, 1).unwrap(), "c");
+        assert_eq!(read_n_bytes(input, 0).unwrap(), "");
+    }
+
+    #[test]
+    fn test_read_remaining_after() {
+        for response in ["abcd", "abcd\n", "abcd\r\n"] {
+            let input = &mut &*response;
+            assert_eq!(read_remaining_after(input, 4).unwrap(), "abcd");
+            assert!(input.is_empty());
+        }
+
+        let input = &mut "abcde\n";
+        assert!(read_remaining_after(input, 4).is_err());
+        assert_eq!(*input, "abcde\n");
+        assert!(read_remaining_after(&mut "abc", 4).is_err());
+        assert!(read_remaining_after(&mut "abcd\n\n", 4).is_err());
+    }
+
     #[test]
     fn test_match_literal() {
         let input = &mut "1234";
@@ -185,12 +744,136 @@ mod tests {
         assert!(check_empty(input).is_ok());
     }
 
+    #[test]
+    fn test_match_header() {
+        for (header, response) in [
+            (":VOLT", ":VOLT 1"),
+            (":VOLT", "VOLT 1"),
+            ("VOLT", ":VOLT 1"),
+            ("VOLT", "VOLT 1"),
+        ] {
+            let input = &mut &*response;
+            assert!(match_header(input, header).is_ok(), "{header} {response}");
+            assert_eq!(*input, " 1");
+        }
+        let input = &mut "::VOLT";
+        assert!(match_header(input, "VOLT").is_err());
+        assert_eq!(*input, "::VOLT");
+    }
+
+    #[test]
+    fn test_read_until_or_end() {
+        let input = &mut "12,34";
+        assert_eq!(read_until_or_end(input, ','), "12");
+        assert_eq!(read_until_or_end(input, ','), "34");
+        assert!(check_empty(input).is_ok());
+        assert_eq!(read_until_or_end(input, ','), "");
+
+        let input = &mut "1µ2";
+        assert_eq!(read_until_or_end(input, 'µ'), "1");
+        assert_eq!(*input, "2");
+    }
+
+    #[test]
+    fn test_match_one_of() {
+        let input = &mut "OFF,ON";
+        assert_eq!(match_one_of(input, &["ON", "OFF"]).unwrap(), 1);
+        assert_eq!(*input, ",ON");
+        assert!(match_literal(input, ",").is_ok());
+        assert_eq!(match_one_of(input, &["ON", "OFF"]).unwrap(), 0);
+        assert!(check_empty(input).is_ok());
+
+        let input = &mut "AUTO";
+        let error = match_one_of(input, &["ON", "OFF"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Received data does not match expected format: Expected one of [\"ON\", \"OFF\"], not matched `AUTO`"
+        );
+        assert_eq!(*input, "AUTO");
+    }
+
+    #[test]
+    fn test_match_keyword() {
+        for response in ["VOLT", "VOLTA", "VOLTAGE", "volt", "Voltage"] {
+            let input = &mut &*response;
+            assert!(match_keyword(input, "VOLTage").is_ok(), "{response}");
+            assert!(input.is_empty());
+        }
+
+        let input = &mut "VOLTAGE:DC";
+        assert!(match_keyword(input, "VOLTage").is_ok());
+        assert_eq!(*input, ":DC");
+
+        let input = &mut "VOL";
+        assert!(match_keyword(input, "VOLTage").is_err());
+        assert_eq!(*input, "VOL");
+
+        let input = &mut "CURR";
+        assert!(match_keyword(input, "CURR").is_ok());
+        assert!(match_keyword(&mut "CURRENT", "CURR").is_err());
+
+        let input = &mut "VOLTS";
+        assert!(match_keyword(input, "VOLTage").is_err());
+        assert_eq!(*input, "VOLTS");
+        assert!(match_keyword(&mut "VOLTAGES", "VOLTage").is_err());
+
+        let input = &mut "off";
+        assert!(matches!(
+            match_keyword(input, "auto"),
+            Err(Error::InvalidParameter(_))
+        ));
+        assert_eq!(*input, "off");
+    }
+
+    #[test]
+    fn test_peek() {
+        let input = &mut "VOLT 1.5";
+        assert_eq!(peek_prefix(input, 4), "VOLT");
+        assert_eq!(peek_prefix(input, 20), "VOLT 1.5");
+        assert_eq!(peek_prefix(input, 0), "");
+        assert!(starts_with_literal(input, "VOLT"));
+        assert!(!starts_with_literal(input, "CURR"));
+        assert_eq!(*input, "VOLT 1.5");
+
+        let input = &mut "µA";
+        assert_eq!(peek_prefix(input, 1), "");
+        assert_eq!(peek_prefix(input, 2), "µ");
+        assert_eq!(*input, "µA");
+    }
+
     #[test]
     fn test_read_until() {
         let input = &mut "12,34";
         assert_eq!(read_until(input, ',').unwrap(), "12");
         assert!(match_literal(input, "34").is_ok());
         assert!(check_empty(input).is_ok());
+
+        let input = &mut "12";
+        assert!(read_until(input, ',').is_err());
+        assert_eq!(*input, "12");
+    }
+
+    #[test]
+    fn test_read_line() {
+        let input = &mut "12\n34\r\n56";
+        assert_eq!(read_line(input).unwrap(), "12");
+        assert_eq!(read_line(input).unwrap(), "34\r");
+        assert_eq!(read_line(input).unwrap(), "56");
+        assert!(check_empty(input).is_ok());
+        assert!(read_line(input).is_err());
+
+        let input = &mut "\n";
+        assert_eq!(read_line(input).unwrap(), "");
+        assert!(check_empty(input).is_ok());
+    }
+
+    #[test]
+    fn test_read_line_crlf() {
+        let input = &mut "12\r\n34\n56\r";
+        assert_eq!(read_line_crlf(input).unwrap(), "12");
+        assert_eq!(read_line_crlf(input).unwrap(), "34");
+        assert_eq!(read_line_crlf(input).unwrap(), "56");
+        assert!(check_empty(input).is_ok());
     }
 
     #[test]
@@ -203,6 +886,15 @@ mod tests {
         assert!(check_empty(input).is_ok());
     }
 
+    #[test]
+    fn test_skip_whitespace() {
+        let input = &mut " \t 12 34";
+        skip_whitespace(input);
+        assert_eq!(*input, "12 34");
+        skip_whitespace(input);
+        assert_eq!(*input, "12 34");
+    }
+
     #[test]
     fn test_read_all() {
         let input = &mut "12,34\nasdf";
@@ -0,0 +1,415 @@
+use std::marker::PhantomData;
+
+use crate::{
+    Result, ScpiDeserialize, ScpiRequest, ScpiSerialize, match_literal, read_while, skip_whitespace,
+};
+
+/// Surrogate value reported for overrange measurements (`9.9E37`),
+/// SCPI additionally uses `9.91E37` for invalid readings, both are treated as overrange.
+pub const OVERRANGE: f64 = 9.9e37;
+
+/// Result of a `MEASure?`/`FETCh?` query with an optional unit suffix, e.g. `1.5E-3 V`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub value: f64,
+    pub unit: Option<String>,
+    /// Whether the instrument reported the overrange surrogate [`OVERRANGE`] (of either sign).
+    pub overrange: bool,
+}
+
+impl Measurement {
+    pub fn new(value: f64) -> Self {
+        Self {
+            value,
+            unit: None,
+            overrange: value.abs() >= OVERRANGE,
+        }
+    }
+
+    /// The measured value, `None` if overrange.
+    pub fn valid_value(&self) -> Option<f64> {
+        (!self.overrange).then_some(self.value)
+    }
+}
+
+impl ScpiSerialize for Measurement {
+    fn serialize(&self, out: &mut String) {
+        self.value.serialize(out);
+        if let Some(unit) = &self.unit {
+            out.push(' ');
+            out.push_str(unit);
+        }
+    }
+}
+
+impl ScpiDeserialize for Measurement {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut measurement = Self::new(f64::deserialize(input)?);
+
+        let mut cursor = *input;
+        skip_whitespace(&mut cursor);
+        let unit = read_while(&mut cursor, |c| c.is_ascii_alphabetic() || c == '%');
+        if !unit.is_empty() {
+            measurement.unit = Some(unit.to_string());
+            *input = cursor;
+        }
+        Ok(measurement)
+    }
+}
+
+/// Value followed by a status or quality code, e.g. `1.5,0`.
+///
+/// The status may be any type, e.g. an integer or an enum defined with
+/// [`scpi_enum!`](crate::scpi_enum).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WithStatus<T, S> {
+    pub value: T,
+    pub status: S,
+}
+
+impl<T: ScpiSerialize, S: ScpiSerialize> ScpiSerialize for WithStatus<T, S> {
+    fn serialize(&self, out: &mut String) {
+        self.value.serialize(out);
+        out.push(',');
+        self.status.serialize(out);
+    }
+
+    fn serialized_len(&self) -> usize {
+        self.value.serialized_len() + 1 + self.status.serialized_len()
+    }
+}
+
+impl<T: ScpiDeserialize, S: ScpiDeserialize> ScpiDeserialize for WithStatus<T, S> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut cursor = *input;
+        let value = T::deserialize(&mut cursor)?;
+        match_literal(&mut cursor, ",")?;
+        let status = S::deserialize(&mut cursor)?;
+        *input = cursor;
+        Ok(Self { value, status })
+    }
+}
+
+/// Measurement function node, e.g. `VOLT:DC`, selecting the function of
+/// [`Measure`], [`Configure`], [`Read`] and [`Fetch`].
+pub trait MeasurementFunction {
+    const NODE: &'static str;
+}
+
+macro_rules! impl_measurement_function {
+    ($(#[$meta:meta])* $name:ident, $node:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name;
+
+        impl MeasurementFunction for $name {
+            const NODE: &'static str = $node;
+        }
+    };
+}
+
+impl_measurement_function!(
+    /// DC voltage, `VOLTage:DC`.
+    VoltageDc,
+    "VOLT:DC"
+);
+impl_measurement_function!(
+    /// AC voltage, `VOLTage:AC`.
+    VoltageAc,
+    "VOLT:AC"
+);
+impl_measurement_function!(
+    /// DC current, `CURRent:DC`.
+    CurrentDc,
+    "CURR:DC"
+);
+impl_measurement_function!(
+    /// AC current, `CURRent:AC`.
+    CurrentAc,
+    "CURR:AC"
+);
+impl_measurement_function!(
+    /// Two-wire resistance, `RESistance`.
+    Resistance,
+    "RES"
+);
+impl_measurement_function!(
+    /// Frequency, `FREQuency`.
+    Frequency,
+    "FREQ"
+);
+
+/// Expected range and resolution of a [`Measure`] or [`Configure`], both default
+/// to the instrument's choice, usually autorange.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MeasurementParameters {
+    pub range: Option<f64>,
+    pub resolution: Option<f64>,
+}
+
+/// A resolution without range is sent with the range `DEF`, as parameters are positional.
+impl ScpiSerialize for MeasurementParameters {
+    fn serialize(&self, out: &mut String) {
+        match (self.range, self.resolution) {
+            (None, None) => {}
+            (range, resolution) => {
+                out.push(' ');
+                match range {
+                    Some(range) => range.serialize(out),
+                    None => out.push_str("DEF"),
+                }
+                if let Some(resolution) = resolution {
+                    out.push(',');
+                    resolution.serialize(out);
+                }
+            }
+        }
+    }
+}
+
+macro_rules! measurement_command {
+    ($(#[$meta:meta])* $name:ident, $root:literal, $suffix:literal, parameters) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub struct $name<F: MeasurementFunction> {
+            pub parameters: MeasurementParameters,
+            function: PhantomData<F>,
+        }
+
+        impl<F: MeasurementFunction> $name<F> {
+            pub fn new() -> Self {
+                Self::with_parameters(MeasurementParameters::default())
+            }
+
+            pub fn with_parameters(parameters: MeasurementParameters) -> Self {
+                Self {
+                    parameters,
+                    function: PhantomData,
+                }
+            }
+        }
+
+        impl<F: MeasurementFunction> Default for $name<F> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<F: MeasurementFunction> ScpiSerialize for $name<F> {
+            fn serialize(&self, out: &mut String) {
+                out.push_str($root);
+                out.push_str(F::NODE);
+                out.push_str($suffix);
+                self.parameters.serialize(out);
+            }
+        }
+    };
+
+    ($(#[$meta:meta])* $name:ident, $root:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name<F: MeasurementFunction>(PhantomData<F>);
+
+        impl<F: MeasurementFunction> $name<F> {
+            pub fn new() -> Self {
+                Self(PhantomData)
+            }
+        }
+
+        impl<F: MeasurementFunction> Default for $name<F> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<F: MeasurementFunction> ScpiSerialize for $name<F> {
+            fn serialize(&self, out: &mut String) {
+                out.push_str($root);
+                out.push_str(F::NODE);
+                out.push('?');
+            }
+        }
+    };
+}
+
+measurement_command!(
+    /// Configure the function and take a reading in one step, e.g. `MEAS:VOLT:DC? 10,0.001`.
+    Measure,
+    "MEAS:",
+    "?",
+    parameters
+);
+measurement_command!(
+    /// Configure the function without taking a reading, e.g. `CONF:VOLT:DC 10`.
+    Configure,
+    "CONF:",
+    "",
+    parameters
+);
+measurement_command!(
+    /// Trigger a reading of the configured function and return it, e.g. `READ:VOLT:DC?`.
+    Read,
+    "READ:"
+);
+measurement_command!(
+    /// Return the last reading without triggering, e.g. `FETC:VOLT:DC?`.
+    Fetch,
+    "FETC:"
+);
+
+impl<F: MeasurementFunction> ScpiRequest for Measure<F> {
+    type Response = Measurement;
+}
+
+impl<F: MeasurementFunction> ScpiRequest for Read<F> {
+    type Response = Measurement;
+}
+
+impl<F: MeasurementFunction> ScpiRequest for Fetch<F> {
+    type Response = Measurement;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_measurement() {
+        let measurement = Measurement::deserialize_complete("1.5E-3").unwrap();
+        assert_eq!(measurement, Measurement::new(1.5e-3));
+        assert_eq!(measurement.valid_value(), Some(1.5e-3));
+
+        let measurement = Measurement::deserialize_complete("-2.5 V").unwrap();
+        assert_eq!(measurement.value, -2.5);
+        assert_eq!(measurement.unit.as_deref(), Some("V"));
+        assert!(!measurement.overrange);
+
+        let measurement = Measurement::deserialize_complete("1E3HZ").unwrap();
+        assert_eq!(measurement.unit.as_deref(), Some("HZ"));
+
+        let input = &mut "1.5 ,2";
+        let measurement = Measurement::deserialize(input).unwrap();
+        assert_eq!(measurement.unit, None);
+        assert_eq!(*input, " ,2");
+    }
+
+    #[test]
+    fn deserialize_overrange() {
+        let measurement = Measurement::deserialize_complete("9.9E37").unwrap();
+        assert!(measurement.overrange);
+        assert_eq!(measurement.valid_value(), None);
+
+        assert!(
+            Measurement::deserialize_complete("-9.9E37")
+                .unwrap()
+                .overrange
+        );
+        assert!(
+            Measurement::deserialize_complete("+9.91E+37 VDC")
+                .unwrap()
+                .overrange
+        );
+        assert!(
+            !Measurement::deserialize_complete("9.8E37")
+                .unwrap()
+                .overrange
+        );
+    }
+
+    #[test]
+    fn serialize_measurement() {
+        let mut measurement = Measurement::new(1.5);
+        assert_eq!(measurement.serialize_to_string(), "1.5");
+        measurement.unit = Some("V".to_string());
+        assert_eq!(measurement.serialize_to_string(), "1.5 V");
+    }
+
+    crate::scpi_enum!(
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum Quality {
+            Valid => "0",
+            Overload => "1",
+            Unsettled => "2",
+        }
+    );
+
+    #[test]
+    fn with_status() {
+        let reading = WithStatus::<f64, Quality>::deserialize_complete("1.5,0").unwrap();
+        assert_eq!(
+            reading,
+            WithStatus {
+                value: 1.5,
+                status: Quality::Valid
+            }
+        );
+        let reading = WithStatus {
+            value: -2.5e-3,
+            status: Quality::Overload,
+        };
+        assert_eq!(reading.serialize_to_string(), "-0.0025,1");
+        assert_eq!(reading.serialized_len(), 9);
+        crate::assert_scpi_roundtrip!(reading);
+
+        let input = &mut "1.5,3";
+        assert!(WithStatus::<f64, Quality>::deserialize(input).is_err());
+        assert_eq!(*input, "1.5,3");
+        assert!(WithStatus::<f64, Quality>::deserialize_complete("1.5").is_err());
+
+        let reading = WithStatus::<Measurement, i32>::deserialize_complete("1E3 HZ,+4").unwrap();
+        assert_eq!(reading.value.unit.as_deref(), Some("HZ"));
+        assert_eq!(reading.status, 4);
+    }
+
+    #[test]
+    fn measurement_commands() {
+        assert_eq!(
+            Measure::<VoltageDc>::new().serialize_to_string(),
+            "MEAS:VOLT:DC?"
+        );
+        let parameters = MeasurementParameters {
+            range: Some(10.0),
+            resolution: Some(0.001),
+        };
+        assert_eq!(
+            Measure::<VoltageDc>::with_parameters(parameters).serialize_to_string(),
+            "MEAS:VOLT:DC? 10,0.001"
+        );
+        let parameters = MeasurementParameters {
+            range: None,
+            resolution: Some(0.001),
+        };
+        assert_eq!(
+            Configure::<VoltageDc>::with_parameters(parameters).serialize_to_string(),
+            "CONF:VOLT:DC DEF,0.001"
+        );
+        let parameters = MeasurementParameters {
+            range: Some(10.0),
+            resolution: None,
+        };
+        assert_eq!(
+            Configure::<VoltageDc>::with_parameters(parameters).serialize_to_string(),
+            "CONF:VOLT:DC 10"
+        );
+        assert_eq!(
+            Read::<VoltageDc>::new().serialize_to_string(),
+            "READ:VOLT:DC?"
+        );
+        assert_eq!(
+            Fetch::<VoltageDc>::new().serialize_to_string(),
+            "FETC:VOLT:DC?"
+        );
+        assert_eq!(
+            Fetch::<Resistance>::new().serialize_to_string(),
+            "FETC:RES?"
+        );
+
+        let response: <Measure<VoltageDc> as ScpiRequest>::Response =
+            ScpiDeserialize::deserialize_complete("-1.234E-03").unwrap();
+        assert_eq!(response, Measurement::new(-1.234e-3));
+        crate::assert_scpi_roundtrip!(response);
+        let response: <Fetch<VoltageDc> as ScpiRequest>::Response =
+            ScpiDeserialize::deserialize_complete("+9.9E37").unwrap();
+        assert!(response.overrange);
+    }
+}
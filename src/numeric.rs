@@ -0,0 +1,808 @@
+use std::fmt::{Binary, Display, Octal, UpperExp, UpperHex, Write};
+use std::num::ParseIntError;
+use std::ops::Neg;
+
+use crate::primitives::float_prefix;
+use crate::{Error, Result, ScpiDeserialize, ScpiRequest, ScpiSerialize, match_literal, scpi_enum};
+
+/// Numeric representations defined by IEEE 488.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericForm {
+    /// Integer, e.g. `13`.
+    Nr1,
+    /// Fixed point with the given number of decimal places, e.g. `12.50`.
+    Nr2(usize),
+    /// Mantissa and exponent, e.g. `1.25E1`.
+    Nr3,
+}
+
+/// Serializes a (floating point) value in a fixed numeric form,
+/// instead of the uncontrolled `to_string` representation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberFormat<T> {
+    pub value: T,
+    pub form: NumericForm,
+}
+
+impl<T> NumberFormat<T> {
+    pub fn new(value: T, form: NumericForm) -> Self {
+        Self { value, form }
+    }
+}
+
+/// Integers are converted to `f64` as well, so NR2 always emits the requested decimals.
+/// A value rounding to zero is emitted without sign, e.g. `0` instead of `-0` for `-0.2` in NR1.
+impl<T: Into<f64> + Copy + UpperExp> ScpiSerialize for NumberFormat<T> {
+    fn serialize(&self, out: &mut String) {
+        let value: f64 = self.value.into();
+        let formatted = match self.form {
+            NumericForm::Nr1 => format!("{value:.0}"),
+            NumericForm::Nr2(decimals) => format!("{value:.decimals$}"),
+            // the exponent form of `T` keeps the shortest representation, e.g. of an `f32`
+            NumericForm::Nr3 if value == 0.0 => "0E0".to_string(),
+            NumericForm::Nr3 => format!("{:E}", self.value),
+        };
+        match formatted.strip_prefix('-') {
+            Some(magnitude) if magnitude.chars().all(|c| c == '0' || c == '.') => {
+                out.push_str(magnitude)
+            }
+            _ => out.push_str(&formatted),
+        }
+    }
+}
+
+/// Notation of [`Precision`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    /// Fixed point, e.g. `0.0000000200`. Integer digits beyond the significant ones
+    /// are rounded to zero, e.g. `1230` for 1234.5678 with 3 digits.
+    Fixed,
+    /// Mantissa and exponent, e.g. `2.00E-8`.
+    Scientific,
+}
+
+/// Serializes a floating point value with a limited number of significant digits,
+/// for instruments rejecting overly long numeric fields.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Precision<T> {
+    pub value: T,
+    /// Significant digits, at least one.
+    pub digits: usize,
+    pub notation: Notation,
+}
+
+impl<T> Precision<T> {
+    pub fn new(value: T, digits: usize, notation: Notation) -> Self {
+        Self {
+            value,
+            digits,
+            notation,
+        }
+    }
+}
+
+impl<T: Into<f64> + Copy> ScpiSerialize for Precision<T> {
+    fn serialize(&self, out: &mut String) {
+        let value: f64 = self.value.into();
+        let digits = self.digits.max(1);
+        match self.notation {
+            Notation::Scientific => write!(out, "{:.*E}", digits - 1, value),
+            Notation::Fixed if !value.is_finite() => write!(out, "{value}"),
+            Notation::Fixed => {
+                // round first, rounding may carry into a new digit, e.g. 9.99 to 10
+                let scientific = format!("{:.*E}", digits - 1, value);
+                let (_, exponent) = scientific.split_once('E').unwrap();
+                let magnitude: isize = exponent.parse().unwrap();
+                let rounded: f64 = scientific.parse().unwrap();
+                let decimals = (digits as isize - 1 - magnitude).max(0) as usize;
+                write!(out, "{:.*}", decimals, rounded)
+            }
+        }
+        .unwrap();
+    }
+}
+
+/// Non-decimal bases of IEEE 488.2 numeric data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericRadix {
+    /// `#H1F`
+    Hexadecimal,
+    /// `#B11111`
+    Binary,
+    /// `#Q37`
+    Octal,
+}
+
+impl NumericRadix {
+    fn prefix(&self) -> &'static str {
+        match self {
+            NumericRadix::Hexadecimal => "#H",
+            NumericRadix::Binary => "#B",
+            NumericRadix::Octal => "#Q",
+        }
+    }
+
+    fn base(&self) -> u32 {
+        match self {
+            NumericRadix::Hexadecimal => 16,
+            NumericRadix::Binary => 2,
+            NumericRadix::Octal => 8,
+        }
+    }
+}
+
+/// Unsigned integers representable by [`Radix`].
+pub trait RadixInteger: UpperHex + Binary + Octal + Sized {
+    fn from_str_radix(digits: &str, radix: u32) -> std::result::Result<Self, ParseIntError>;
+}
+
+macro_rules! impl_radix_integer {
+    ($($type:ty),*) => {
+        $(
+            impl RadixInteger for $type {
+                fn from_str_radix(digits: &str, radix: u32) -> std::result::Result<Self, ParseIntError> {
+                    <$type>::from_str_radix(digits, radix)
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_integer!(u8, u16, u32, u64, u128);
+
+/// Integer in a non-decimal base, e.g. `#H1F`, for instruments expecting register values in that form.
+/// Deserialization accepts all bases, with case-insensitive prefix and digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Radix<T> {
+    pub value: T,
+    pub base: NumericRadix,
+}
+
+impl<T> Radix<T> {
+    pub fn new(value: T, base: NumericRadix) -> Self {
+        Self { value, base }
+    }
+}
+
+impl<T: RadixInteger> ScpiSerialize for Radix<T> {
+    fn serialize(&self, out: &mut String) {
+        out.push_str(self.base.prefix());
+        match self.base {
+            NumericRadix::Hexadecimal => write!(out, "{:X}", self.value),
+            NumericRadix::Binary => write!(out, "{:b}", self.value),
+            NumericRadix::Octal => write!(out, "{:o}", self.value),
+        }
+        .unwrap();
+    }
+}
+
+impl<T: RadixInteger> ScpiDeserialize for Radix<T> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let base = match input.get(..2).map(str::to_ascii_uppercase).as_deref() {
+            Some("#H") => NumericRadix::Hexadecimal,
+            Some("#B") => NumericRadix::Binary,
+            Some("#Q") => NumericRadix::Octal,
+            _ => {
+                return Err(Error::ResponseDecoding(format!(
+                    "Expected `#H`, `#B` or `#Q`, not matched `{input}`"
+                )));
+            }
+        };
+        let digits = &input[2..];
+        let len = digits
+            .find(|c: char| !c.is_digit(base.base()))
+            .unwrap_or(digits.len());
+        let value = T::from_str_radix(&digits[..len], base.base()).map_err(|e| {
+            Error::ResponseDecoding(format!("Failed to parse `{}`: {e}", &input[..2 + len]))
+        })?;
+        *input = &digits[len..];
+        Ok(Self { value, base })
+    }
+}
+
+scpi_enum!(
+    /// Numeric limits of a setting, as parameter or for [`LimitQuery`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Limit {
+        Minimum => "MIN",
+        Maximum => "MAX",
+        Default => "DEF",
+    }
+);
+
+/// Query a limit of a setting instead of its value, e.g. `SOUR:VOLT? MAX`
+/// wrapping the query `SOUR:VOLT?`. The response is that of the wrapped query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitQuery<Q> {
+    pub query: Q,
+    pub limit: Limit,
+}
+
+impl<Q> LimitQuery<Q> {
+    pub fn new(query: Q, limit: Limit) -> Self {
+        Self { query, limit }
+    }
+}
+
+impl<Q: ScpiSerialize> ScpiSerialize for LimitQuery<Q> {
+    fn serialize(&self, out: &mut String) {
+        self.query.serialize(out);
+        out.push(' ');
+        self.limit.serialize(out);
+    }
+}
+
+impl<Q: ScpiRequest> ScpiRequest for LimitQuery<Q> {
+    type Response = Q::Response;
+}
+
+/// Serializes numbers with an explicit sign, i.e. `+` for non-negative values.
+/// Deserialization accepts values with and without a leading `+`, but no second sign.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SignedExplicit<T>(pub T);
+
+impl<T: Display> ScpiSerialize for SignedExplicit<T> {
+    fn serialize(&self, out: &mut String) {
+        write!(out, "{:+}", self.0).unwrap();
+    }
+}
+
+impl<T: ScpiDeserialize> ScpiDeserialize for SignedExplicit<T> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut cursor = *input;
+        if let Some(rest) = cursor.strip_prefix('+') {
+            if rest.starts_with(['+', '-']) {
+                return Err(Error::ResponseDecoding(format!(
+                    "Expected a single sign in `{}`",
+                    &input[..2]
+                )));
+            }
+            cursor = rest;
+        }
+        let value = T::deserialize(&mut cursor)?;
+        *input = cursor;
+        Ok(Self(value))
+    }
+}
+
+scpi_enum!(
+    /// Polarity keyword, e.g. of a trigger slope or an offset.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Polarity {
+        Positive => keyword "POSitive",
+        Negative => keyword "NEGative",
+    }
+);
+
+/// Magnitude with its polarity given as keyword instead of a sign, e.g. `POSITIVE,1.5`.
+/// The keyword is serialized in its long form, abbreviations like `POS,1.5` are accepted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Signed<T> {
+    pub polarity: Polarity,
+    pub magnitude: T,
+}
+
+impl<T> Signed<T> {
+    pub fn new(polarity: Polarity, magnitude: T) -> Self {
+        Self {
+            polarity,
+            magnitude,
+        }
+    }
+}
+
+impl<T: Neg<Output = T> + Copy> Signed<T> {
+    /// The magnitude, negated for [`Polarity::Negative`].
+    pub fn value(&self) -> T {
+        match self.polarity {
+            Polarity::Positive => self.magnitude,
+            Polarity::Negative => -self.magnitude,
+        }
+    }
+}
+
+impl<T: ScpiSerialize> ScpiSerialize for Signed<T> {
+    fn serialize(&self, out: &mut String) {
+        self.polarity.serialize(out);
+        out.push(',');
+        self.magnitude.serialize(out);
+    }
+}
+
+impl<T: ScpiDeserialize> ScpiDeserialize for Signed<T> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let polarity = Polarity::deserialize(input)?;
+        match_literal(input, ",")?;
+        let magnitude = T::deserialize(input)?;
+        Ok(Self::new(polarity, magnitude))
+    }
+}
+
+/// Floating point number with a configurable decimal separator, e.g. `1,5` for
+/// `Decimal<','>` as sent by instruments configured for a European locale.
+/// The separator must be an ASCII character, others fail to compile:
+///
+/// ```compile_fail
+/// use scpi_client::ScpiDeserialize;
+/// use scpi_client::numeric::Decimal;
+///
+/// Decimal::<'٫'>::deserialize_complete("1٫5");
+/// ```
+///
+/// The default `Decimal<'.'>` behaves like `f64`. With a comma as decimal separator,
+/// lists are usually delimited by `;` instead, i.e. `Delimited<Decimal<','>, ';'>`
+/// for `1,5;-2,25`, see [`Delimited`](crate::containers::Delimited).
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Decimal<const SEPARATOR: char = '.'>(pub f64);
+
+impl<const SEPARATOR: char> From<f64> for Decimal<SEPARATOR> {
+    fn from(value: f64) -> Self {
+        Self(value)
+    }
+}
+
+impl<const SEPARATOR: char> From<Decimal<SEPARATOR>> for f64 {
+    fn from(value: Decimal<SEPARATOR>) -> Self {
+        value.0
+    }
+}
+
+impl<const SEPARATOR: char> ScpiSerialize for Decimal<SEPARATOR> {
+    fn serialize(&self, out: &mut String) {
+        let start = out.len();
+        self.0.serialize(out);
+        if SEPARATOR != '.' {
+            let localized = out[start..].replace('.', &SEPARATOR.to_string());
+            out.replace_range(start.., &localized);
+        }
+    }
+}
+
+impl<const SEPARATOR: char> ScpiDeserialize for Decimal<SEPARATOR> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        const { assert!(SEPARATOR.is_ascii(), "decimal separator must be ASCII") };
+        if SEPARATOR == '.' {
+            return f64::deserialize(input).map(Self);
+        }
+        // translate the number to the `.` notation, a `.` itself ends the number
+        let len = input
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == SEPARATOR || c == '+' || c == '-'))
+            .unwrap_or(input.len());
+        let translated = input[..len].replace(SEPARATOR, ".");
+        let mut cursor = translated.as_str();
+        let value = f64::deserialize(&mut cursor)?;
+        // ASCII only, as asserted above, so byte offsets are the same in both
+        let consumed = translated.len() - cursor.len();
+        *input = &input[consumed..];
+        Ok(Self(value))
+    }
+}
+
+/// Floating point number decomposed into mantissa and decimal exponent as written,
+/// e.g. `1.5E3` into `1.5` and `3`, for custom scaling without rounding through a single `f64`.
+/// A missing exponent is `0`, non-finite values are rejected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MantissaExponent {
+    pub mantissa: f64,
+    pub exponent: i32,
+}
+
+impl MantissaExponent {
+    /// The value `mantissa * 10^exponent`.
+    pub fn value(&self) -> f64 {
+        self.mantissa * 10f64.powi(self.exponent)
+    }
+}
+
+impl ScpiSerialize for MantissaExponent {
+    fn serialize(&self, out: &mut String) {
+        self.mantissa.serialize(out);
+        if self.exponent != 0 {
+            write!(out, "E{}", self.exponent).unwrap();
+        }
+    }
+}
+
+impl ScpiDeserialize for MantissaExponent {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let prefix = float_prefix(input);
+        let (mantissa, exponent) = prefix
+            .split_once(['e', 'E'])
+            .map_or((prefix, None), |(mantissa, exponent)| {
+                (mantissa, Some(exponent))
+            });
+        if !mantissa.contains(|c: char| c.is_ascii_digit()) {
+            return Err(Error::ResponseDecoding(format!(
+                "Expected finite number, not matched `{input}`"
+            )));
+        }
+        let mantissa = mantissa.parse().map_err(|e| {
+            Error::ResponseDecoding(format!("Failed to parse mantissa `{mantissa}`: {e}"))
+        })?;
+        let exponent = match exponent {
+            Some(exponent) => exponent
+                .parse()
+                .map_err(|_| Error::OutOfRange(format!("Exponent `{exponent}` exceeds i32")))?,
+            None => 0,
+        };
+        *input = &input[prefix.len()..];
+        Ok(Self { mantissa, exponent })
+    }
+}
+
+/// Value validated against an inclusive range `[min, max]` on construction,
+/// catching out of range parameters before they are sent to an instrument.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounded<T> {
+    value: T,
+    min: T,
+    max: T,
+}
+
+impl<T: PartialOrd + Display> Bounded<T> {
+    pub fn new(value: T, min: T, max: T) -> Result<Self> {
+        if min <= value && value <= max {
+            Ok(Self { value, min, max })
+        } else {
+            Err(Error::OutOfRange(format!(
+                "{value} is not within [{min}, {max}]"
+            )))
+        }
+    }
+}
+
+impl<T> Bounded<T> {
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn min(&self) -> &T {
+        &self.min
+    }
+
+    pub fn max(&self) -> &T {
+        &self.max
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: ScpiSerialize> ScpiSerialize for Bounded<T> {
+    fn serialize(&self, out: &mut String) {
+        self.value.serialize(out);
+    }
+}
+
+/// Compare floats for equality, treating two NaNs as equal.
+pub fn nan_eq(a: f64, b: f64) -> bool {
+    a == b || (a.is_nan() && b.is_nan())
+}
+
+/// Compare floats within an absolute or relative tolerance, treating two NaNs as equal.
+///
+/// Finite values are equal if their difference is within `tol`, or within
+/// `tol` relative to the larger magnitude. Infinities only equal themselves.
+pub fn approx_eq(a: f64, b: f64, tol: f64) -> bool {
+    if nan_eq(a, b) {
+        return true;
+    }
+    if !a.is_finite() || !b.is_finite() {
+        return false;
+    }
+    let difference = (a - b).abs();
+    difference <= tol || difference <= tol * a.abs().max(b.abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_separator() {
+        use crate::containers::Delimited;
+
+        assert_eq!(Decimal::<'.'>(1.5).serialize_to_string(), "1.5");
+        assert_eq!(Decimal::<','>(-1.5).serialize_to_string(), "-1,5");
+        assert_eq!(Decimal::<','>(2.0).serialize_to_string(), "2");
+
+        let list = Delimited::<Decimal, ','>::deserialize_complete("1.5,-2.25E3").unwrap();
+        assert_eq!(list.0, vec![Decimal(1.5), Decimal(-2.25e3)]);
+        let list = Delimited::<Decimal<','>, ';'>::deserialize_complete("1,5;-2,25E3;7").unwrap();
+        assert_eq!(list.0, vec![Decimal(1.5), Decimal(-2.25e3), Decimal(7.0)]);
+        assert_eq!(list.serialize_to_string(), "1,5;-2250;7");
+
+        assert!(Decimal::<','>::deserialize_complete("1.5").is_err());
+        assert!(
+            Delimited::<Decimal, ','>::deserialize_complete("1,5")
+                .unwrap()
+                .0
+                .len()
+                == 2
+        );
+        crate::assert_scpi_roundtrip!(Decimal::<','>(-0.125));
+    }
+
+    #[test]
+    fn mantissa_exponent() {
+        let parse = MantissaExponent::deserialize_complete;
+        let decomposed = parse("1.5E3").unwrap();
+        assert_eq!((decomposed.mantissa, decomposed.exponent), (1.5, 3));
+        assert_eq!(decomposed.value(), 1500.0);
+        let decomposed = parse("1.5").unwrap();
+        assert_eq!((decomposed.mantissa, decomposed.exponent), (1.5, 0));
+        let decomposed = parse("2E-8").unwrap();
+        assert_eq!((decomposed.mantissa, decomposed.exponent), (2.0, -8));
+        let decomposed = parse("-12e+400").unwrap();
+        assert_eq!((decomposed.mantissa, decomposed.exponent), (-12.0, 400));
+        assert!(decomposed.value().is_infinite());
+
+        assert!(parse("NAN").is_err());
+        assert!(parse("E3").is_err());
+        assert!(parse("1E9999999999").is_err());
+        crate::assert_scpi_roundtrip!(decomposed);
+        crate::assert_scpi_roundtrip!(MantissaExponent {
+            mantissa: 1.5,
+            exponent: 0
+        });
+    }
+
+    #[test]
+    fn validate_enums() {
+        Limit::validate().unwrap();
+        Polarity::validate().unwrap();
+    }
+
+    #[test]
+    fn signed() {
+        let positive = Signed::new(Polarity::Positive, 1.5);
+        assert_eq!(positive.serialize_to_string(), "POSITIVE,1.5");
+        assert_eq!(positive.value(), 1.5);
+        crate::assert_scpi_roundtrip!(positive);
+
+        let negative = Signed::new(Polarity::Negative, 2u8);
+        assert_eq!(negative.serialize_to_string(), "NEGATIVE,2");
+        crate::assert_scpi_roundtrip!(negative);
+
+        let parsed = Signed::<f64>::deserialize_complete("NEG,0.25").unwrap();
+        assert_eq!(parsed.value(), -0.25);
+        assert!(Signed::<f64>::deserialize_complete("POS").is_err());
+        assert!(Signed::<f64>::deserialize_complete("UP,1").is_err());
+    }
+
+    #[test]
+    fn serialize_nr1() {
+        assert_eq!(
+            NumberFormat::new(12.7f64, NumericForm::Nr1).serialize_to_string(),
+            "13"
+        );
+        assert_eq!(
+            NumberFormat::new(-3.2f32, NumericForm::Nr1).serialize_to_string(),
+            "-3"
+        );
+        assert_eq!(
+            NumberFormat::new(2e-8f64, NumericForm::Nr1).serialize_to_string(),
+            "0"
+        );
+        assert_eq!(
+            NumberFormat::new(-0.2f64, NumericForm::Nr1).serialize_to_string(),
+            "0"
+        );
+        assert_eq!(
+            NumberFormat::new(-0.0f64, NumericForm::Nr1).serialize_to_string(),
+            "0"
+        );
+        assert_eq!(
+            NumberFormat::new(-7i32, NumericForm::Nr1).serialize_to_string(),
+            "-7"
+        );
+    }
+
+    #[test]
+    fn serialize_nr2() {
+        assert_eq!(
+            NumberFormat::new(12.5f64, NumericForm::Nr2(2)).serialize_to_string(),
+            "12.50"
+        );
+        assert_eq!(
+            NumberFormat::new(-0.125f32, NumericForm::Nr2(1)).serialize_to_string(),
+            "-0.1"
+        );
+        assert_eq!(
+            NumberFormat::new(3f64, NumericForm::Nr2(0)).serialize_to_string(),
+            "3"
+        );
+        assert_eq!(
+            NumberFormat::new(5i32, NumericForm::Nr2(3)).serialize_to_string(),
+            "5.000"
+        );
+        assert_eq!(
+            NumberFormat::new(-0.001f64, NumericForm::Nr2(2)).serialize_to_string(),
+            "0.00"
+        );
+    }
+
+    #[test]
+    fn serialize_nr3() {
+        assert_eq!(
+            NumberFormat::new(12.5f64, NumericForm::Nr3).serialize_to_string(),
+            "1.25E1"
+        );
+        assert_eq!(
+            NumberFormat::new(2e-8f32, NumericForm::Nr3).serialize_to_string(),
+            "2E-8"
+        );
+        assert_eq!(
+            NumberFormat::new(-1500f64, NumericForm::Nr3).serialize_to_string(),
+            "-1.5E3"
+        );
+        assert_eq!(
+            NumberFormat::new(-0.0f64, NumericForm::Nr3).serialize_to_string(),
+            "0E0"
+        );
+    }
+
+    #[test]
+    fn serialize_signed_explicit() {
+        assert_eq!(SignedExplicit(5u32).serialize_to_string(), "+5");
+        assert_eq!(SignedExplicit(0i32).serialize_to_string(), "+0");
+        assert_eq!(SignedExplicit(-5i32).serialize_to_string(), "-5");
+        assert_eq!(SignedExplicit(0.5f32).serialize_to_string(), "+0.5");
+        assert_eq!(SignedExplicit(-0.5f64).serialize_to_string(), "-0.5");
+    }
+
+    #[test]
+    fn deserialize_signed_explicit() {
+        assert_eq!(
+            SignedExplicit::<u32>::deserialize_complete("+5").unwrap(),
+            SignedExplicit(5)
+        );
+        assert_eq!(
+            SignedExplicit::<u32>::deserialize_complete("5").unwrap(),
+            SignedExplicit(5)
+        );
+        assert_eq!(
+            SignedExplicit::<i32>::deserialize_complete("-5").unwrap(),
+            SignedExplicit(-5)
+        );
+        assert_eq!(
+            SignedExplicit::<f32>::deserialize_complete("+0.5").unwrap(),
+            SignedExplicit(0.5)
+        );
+        assert!(SignedExplicit::<u32>::deserialize_complete("-5").is_err());
+        assert!(SignedExplicit::<i32>::deserialize_complete("+-5").is_err());
+        assert!(SignedExplicit::<i32>::deserialize_complete("++5").is_err());
+
+        let mut input = "+x";
+        assert!(SignedExplicit::<i32>::deserialize(&mut input).is_err());
+        assert_eq!(input, "+x");
+    }
+
+    #[test]
+    fn bounded() {
+        assert_eq!(Bounded::new(5u8, 0, 10).unwrap().serialize_to_string(), "5");
+        assert_eq!(*Bounded::new(0u8, 0, 10).unwrap().value(), 0);
+        assert_eq!(*Bounded::new(10u8, 0, 10).unwrap().value(), 10);
+        assert!(Bounded::new(11u8, 0, 10).is_err());
+        assert!(Bounded::new(-1i16, 0, 10).is_err());
+
+        assert!(Bounded::new(-30.0f64, -30.0, 30.0).is_ok());
+        assert!(Bounded::new(30.0f64, -30.0, 30.0).is_ok());
+        assert!(Bounded::new(30.001f64, -30.0, 30.0).is_err());
+        assert!(Bounded::new(f64::NAN, -30.0, 30.0).is_err());
+    }
+
+    #[test]
+    fn compare_nan_eq() {
+        assert!(nan_eq(1.5, 1.5));
+        assert!(nan_eq(f64::NAN, f64::NAN));
+        assert!(nan_eq(f64::INFINITY, f64::INFINITY));
+        assert!(!nan_eq(f64::NAN, 1.5));
+        assert!(!nan_eq(1.5, 1.6));
+    }
+
+    #[test]
+    fn compare_approx_eq() {
+        assert!(approx_eq(1.0, 1.0 + 1e-12, 1e-9));
+        assert!(approx_eq(1e12, 1e12 + 1.0, 1e-9));
+        assert!(approx_eq(f64::NAN, f64::NAN, 1e-9));
+        assert!(approx_eq(f64::NEG_INFINITY, f64::NEG_INFINITY, 1e-9));
+        assert!(!approx_eq(f64::INFINITY, f64::NEG_INFINITY, 1e-9));
+        assert!(!approx_eq(f64::INFINITY, 1e300, 1e-9));
+        assert!(!approx_eq(f64::NAN, 0.0, 1e-9));
+        assert!(!approx_eq(1.0, 1.1, 1e-9));
+        assert!(approx_eq(0.1f32 as f64, 0.1, 1e-6));
+    }
+
+    #[test]
+    fn radix() {
+        assert_eq!(
+            Radix::new(31u8, NumericRadix::Hexadecimal).serialize_to_string(),
+            "#H1F"
+        );
+        assert_eq!(
+            Radix::new(5u16, NumericRadix::Binary).serialize_to_string(),
+            "#B101"
+        );
+        assert_eq!(
+            Radix::new(8u32, NumericRadix::Octal).serialize_to_string(),
+            "#Q10"
+        );
+
+        for base in [
+            NumericRadix::Hexadecimal,
+            NumericRadix::Binary,
+            NumericRadix::Octal,
+        ] {
+            for value in [0u16, 1, 255, u16::MAX] {
+                crate::assert_scpi_roundtrip!(Radix::new(value, base));
+            }
+        }
+
+        assert_eq!(
+            Radix::<u8>::deserialize_complete("#hff").unwrap(),
+            Radix::new(255, NumericRadix::Hexadecimal)
+        );
+        let input = &mut "#B102";
+        assert_eq!(Radix::<u8>::deserialize(input).unwrap().value, 2);
+        assert_eq!(*input, "2");
+
+        assert!(Radix::<u8>::deserialize_complete("#H100").is_err());
+        assert!(Radix::<u8>::deserialize_complete("#H").is_err());
+        assert!(Radix::<u8>::deserialize_complete("#X1").is_err());
+        assert!(Radix::<u8>::deserialize_complete("15").is_err());
+    }
+
+    crate::scpi_command_pair!(struct Voltage, VoltageQuery => "SOUR:VOLT", f32);
+
+    #[test]
+    fn limit_query() {
+        for (limit, expected) in [
+            (Limit::Minimum, "SOUR:VOLT? MIN"),
+            (Limit::Maximum, "SOUR:VOLT? MAX"),
+            (Limit::Default, "SOUR:VOLT? DEF"),
+        ] {
+            assert_eq!(
+                LimitQuery::new(VoltageQuery, limit).serialize_to_string(),
+                expected
+            );
+        }
+        let response: <LimitQuery<VoltageQuery> as ScpiRequest>::Response =
+            ScpiDeserialize::deserialize_complete("3.2E+01").unwrap();
+        assert_eq!(response, 32.0);
+
+        let command = Voltage { value: 1.5 };
+        assert_eq!(command.serialize_to_string(), "SOUR:VOLT 1.5");
+        assert_eq!(Limit::deserialize_complete("DEF").unwrap(), Limit::Default);
+    }
+
+    #[test]
+    fn precision() {
+        let serialize = |value: f64, digits, notation| {
+            Precision::new(value, digits, notation).serialize_to_string()
+        };
+        assert_eq!(serialize(2e-8, 3, Notation::Scientific), "2.00E-8");
+        assert_eq!(serialize(2e-8, 3, Notation::Fixed), "0.0000000200");
+        assert_eq!(serialize(1234.5678, 3, Notation::Fixed), "1230");
+        assert_eq!(serialize(9.99, 2, Notation::Fixed), "10");
+        assert_eq!(serialize(0.0999, 2, Notation::Fixed), "0.10");
+        assert_eq!(serialize(1234.5678, 6, Notation::Fixed), "1234.57");
+        assert_eq!(serialize(-0.015625, 2, Notation::Fixed), "-0.016");
+        assert_eq!(serialize(0.0, 3, Notation::Fixed), "0.00");
+        assert_eq!(serialize(6.02e23, 4, Notation::Scientific), "6.020E23");
+        assert_eq!(serialize(1.5, 0, Notation::Scientific), "2E0");
+        assert_eq!(
+            Precision::new(1.1f32, 4, Notation::Fixed).serialize_to_string(),
+            "1.100"
+        );
+
+        for value in [1.0 / 3.0, -2.5e-9, 6.02e23, 0.0, 987.654321] {
+            for notation in [Notation::Fixed, Notation::Scientific] {
+                let serialized = serialize(value, 5, notation);
+                let result = f64::deserialize_complete(&serialized).unwrap();
+                assert!(approx_eq(value, result, 1e-4), "{serialized}");
+            }
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use regex::Regex;
+use std::fmt::Display;
+use std::num::{IntErrorKind, ParseIntError};
 use std::str::FromStr;
 
-use crate::{Error, Result, ScpiDeserialize, ScpiSerialize, read_exact};
+use crate::{Error, Result, ScpiDeserialize, ScpiSerialize, read_all, read_exact};
 
 pub struct SerializeToString<T: ToString>(T);
 
@@ -14,6 +16,14 @@ impl<T: ToString> ScpiSerialize for SerializeToString<T> {
 pub trait DeserializedWithParse: FromStr {
     /// Return the number of characters to consume from `input` and parse to Self.
     fn prefix_len(input: &str) -> usize;
+
+    /// Map the failure to parse `prefix` into an error.
+    fn parse_error(prefix: &str, error: Self::Err) -> Error
+    where
+        Self::Err: Display,
+    {
+        Error::ResponseDecoding(format!("Failed to parse `{prefix}`: {error}"))
+    }
 }
 
 impl<T> ScpiDeserialize for T
@@ -23,10 +33,7 @@ where
     fn deserialize(input: &mut &str) -> Result<Self> {
         let len = T::prefix_len(input);
         let prefix = read_exact(input, len)?;
-        let result = prefix
-            .parse()
-            .map_err(|e| Error::ResponseDecoding(format!("Failed to parse: {e}")))?;
-        Ok(result)
+        prefix.parse().map_err(|e| T::parse_error(prefix, e))
     }
 }
 
@@ -40,6 +47,7 @@ macro_rules! impl_serialize_to_string {
     };
 }
 
+#[cfg(test)]
 macro_rules! for_numeric_primitives {
     ($callback_macro:ident) => {
         $callback_macro!(u8);
@@ -57,40 +65,268 @@ macro_rules! for_numeric_primitives {
     };
 }
 
-for_numeric_primitives!(impl_serialize_to_string);
+macro_rules! impl_serialize_integer {
+    (unsigned $type:ty) => {
+        impl ScpiSerialize for $type {
+            fn serialize(&self, out: &mut String) {
+                SerializeToString(self).serialize(out);
+            }
+
+            fn serialized_len(&self) -> usize {
+                self.checked_ilog10()
+                    .map_or(1, |digits| digits as usize + 1)
+            }
+        }
+    };
+    (signed $type:ty) => {
+        impl ScpiSerialize for $type {
+            fn serialize(&self, out: &mut String) {
+                SerializeToString(self).serialize(out);
+            }
+
+            fn serialized_len(&self) -> usize {
+                usize::from(*self < 0) + self.unsigned_abs().serialized_len()
+            }
+        }
+    };
+}
+
+impl_serialize_integer!(unsigned u8);
+impl_serialize_integer!(unsigned u16);
+impl_serialize_integer!(unsigned u32);
+impl_serialize_integer!(unsigned u64);
+impl_serialize_integer!(unsigned u128);
+impl_serialize_integer!(signed i8);
+impl_serialize_integer!(signed i16);
+impl_serialize_integer!(signed i32);
+impl_serialize_integer!(signed i64);
+impl_serialize_integer!(signed i128);
+impl_serialize_to_string!(f32);
+impl_serialize_to_string!(f64);
+
+impl ScpiSerialize for char {
+    fn serialize(&self, out: &mut String) {
+        out.push(*self);
+    }
+
+    fn serialized_len(&self) -> usize {
+        self.len_utf8()
+    }
+}
 
 macro_rules! impl_deserialize_with_parse_from_regex {
     ($type:ty, $regex:ident) => {
         impl DeserializedWithParse for $type {
             fn prefix_len(input: &str) -> usize {
-                $regex.find(input).map_or(0, |m| m.end())
+                matched_prefix(&$regex, input).len()
+            }
+        }
+    };
+    (integer $type:ty, $regex:ident) => {
+        impl DeserializedWithParse for $type {
+            fn prefix_len(input: &str) -> usize {
+                matched_prefix(&$regex, input).len()
+            }
+
+            fn parse_error(prefix: &str, error: ParseIntError) -> Error {
+                integer_parse_error(prefix, error, stringify!($type))
             }
         }
     };
 }
 
+/// Distinguish syntactically valid integers exceeding the target type
+/// from input not being an integer at all.
+fn integer_parse_error(prefix: &str, error: ParseIntError, type_name: &str) -> Error {
+    match error.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+            Error::OutOfRange(format!("`{prefix}` exceeds the range of {type_name}"))
+        }
+        IntErrorKind::Empty => {
+            Error::ResponseDecoding(format!("Expected {type_name}, found no digits"))
+        }
+        _ => Error::ResponseDecoding(format!("Invalid digits for {type_name} in `{prefix}`")),
+    }
+}
+
 lazy_static::lazy_static! {
     static ref REGEX_UNSIGNED_INT: Regex = Regex::new(r"^\d+").unwrap();
     static ref REGEX_SIGNED_INT: Regex = Regex::new(r"^[+-]?\d+").unwrap();
-    static ref REGEX_FLOATING_POINT: Regex = Regex::new(r"^[+-]?(?:\d+)?(?:\.\d+)?(?:[eE][+-]?\d+)?").unwrap();
-}
-
-impl_deserialize_with_parse_from_regex!(u8, REGEX_UNSIGNED_INT);
-impl_deserialize_with_parse_from_regex!(u16, REGEX_UNSIGNED_INT);
-impl_deserialize_with_parse_from_regex!(u32, REGEX_UNSIGNED_INT);
-impl_deserialize_with_parse_from_regex!(u64, REGEX_UNSIGNED_INT);
-impl_deserialize_with_parse_from_regex!(u128, REGEX_UNSIGNED_INT);
-impl_deserialize_with_parse_from_regex!(i8, REGEX_SIGNED_INT);
-impl_deserialize_with_parse_from_regex!(i16, REGEX_SIGNED_INT);
-impl_deserialize_with_parse_from_regex!(i32, REGEX_SIGNED_INT);
-impl_deserialize_with_parse_from_regex!(i64, REGEX_SIGNED_INT);
-impl_deserialize_with_parse_from_regex!(i128, REGEX_SIGNED_INT);
+    static ref REGEX_FLOATING_POINT: Regex = Regex::new(r"^[+-]?(?:\d+(?:\.\d*)?|\.\d+)(?:[eE][+-]?\d+)?").unwrap();
+    static ref REGEX_NON_FINITE: Regex = Regex::new(r"^([+-]?)(?i:(nan)|inf(?:inity)?)\b").unwrap();
+}
+
+fn matched_prefix<'a>(regex: &Regex, input: &'a str) -> &'a str {
+    regex.find(input).map_or("", |m| m.as_str())
+}
+
+/// Prefix of `input` considered by the unsigned integer deserialization, e.g. `12` of `12.5`.
+pub fn unsigned_prefix(input: &str) -> &str {
+    matched_prefix(&REGEX_UNSIGNED_INT, input)
+}
+
+/// Prefix of `input` considered by the signed integer deserialization, e.g. `-12` of `-12.5`.
+pub fn signed_prefix(input: &str) -> &str {
+    matched_prefix(&REGEX_SIGNED_INT, input)
+}
+
+/// Prefix of `input` considered by the floating point deserialization, e.g. `1.2` of `1.2.3`.
+pub fn float_prefix(input: &str) -> &str {
+    matched_prefix(&REGEX_FLOATING_POINT, input)
+}
+
+impl_deserialize_with_parse_from_regex!(integer u8, REGEX_UNSIGNED_INT);
+impl_deserialize_with_parse_from_regex!(integer u16, REGEX_UNSIGNED_INT);
+impl_deserialize_with_parse_from_regex!(integer u32, REGEX_UNSIGNED_INT);
+impl_deserialize_with_parse_from_regex!(integer u64, REGEX_UNSIGNED_INT);
+impl_deserialize_with_parse_from_regex!(integer u128, REGEX_UNSIGNED_INT);
+impl_deserialize_with_parse_from_regex!(integer i8, REGEX_SIGNED_INT);
+impl_deserialize_with_parse_from_regex!(integer i16, REGEX_SIGNED_INT);
+impl_deserialize_with_parse_from_regex!(integer i32, REGEX_SIGNED_INT);
+impl_deserialize_with_parse_from_regex!(integer i64, REGEX_SIGNED_INT);
+impl_deserialize_with_parse_from_regex!(integer i128, REGEX_SIGNED_INT);
 impl_deserialize_with_parse_from_regex!(f32, REGEX_FLOATING_POINT);
 impl_deserialize_with_parse_from_regex!(f64, REGEX_FLOATING_POINT);
 
+/// Floating point value additionally accepting the non-finite tokens `NAN`, `INF` and `INFINITY`,
+/// optionally signed and in any case, e.g. for computed results like impedances.
+///
+/// Plain `f64` only accepts finite numbers, so keywords starting like these tokens,
+/// e.g. `INFO`, are never mistaken for a number. A token must be followed by a boundary,
+/// i.e. neither a letter, digit nor `_`. Non-finite values are serialized as `NAN`, `INF` and `-INF`.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct ExtendedFloat(pub f64);
+
+impl ScpiSerialize for ExtendedFloat {
+    fn serialize(&self, out: &mut String) {
+        match self.0 {
+            value if value.is_nan() => out.push_str("NAN"),
+            f64::INFINITY => out.push_str("INF"),
+            f64::NEG_INFINITY => out.push_str("-INF"),
+            value => value.serialize(out),
+        }
+    }
+}
+
+impl ScpiDeserialize for ExtendedFloat {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let Some(captures) = REGEX_NON_FINITE.captures(input) else {
+            return f64::deserialize(input).map(Self);
+        };
+        let value = if captures.get(2).is_some() {
+            f64::NAN
+        } else if &captures[1] == "-" {
+            f64::NEG_INFINITY
+        } else {
+            f64::INFINITY
+        };
+        *input = &input[captures[0].len()..];
+        Ok(Self(value))
+    }
+}
+
+/// Bare (unquoted) text, pushed verbatim without any escaping.
+/// See [`crate::strings`] for quoted and validated text.
+impl ScpiSerialize for str {
+    fn serialize(&self, out: &mut String) {
+        out.push_str(self);
+    }
+
+    fn serialized_len(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Bare (unquoted) text, pushed verbatim.
+impl ScpiSerialize for String {
+    fn serialize(&self, out: &mut String) {
+        out.push_str(self);
+    }
+
+    fn serialized_len(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Bare (unquoted) text, deserialization is greedy and consumes the complete
+/// remaining input. Thus, it always satisfies `deserialize_complete`, but
+/// should only be used for the last field of a response.
+impl ScpiDeserialize for String {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        read_all(input)
+    }
+}
+
+/// Boolean program data, serialized as `ON`/`OFF`.
+///
+/// Deserialization accepts `1`, `0`, `ON`, `OFF` and, as some instruments respond
+/// with them, `TRUE` and `FALSE`, ignoring case. The token must end at the
+/// end of input or a non-alphanumeric character, so e.g. `10` or `ONCE` are rejected
+/// instead of being read partially, while `ON;VOLT 5` leaves `;VOLT 5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Boolean(pub bool);
+
+/// Accepted spellings of [`Boolean`], in order of precedence.
+const BOOLEAN_FORMS: [(&str, bool); 6] = [
+    ("1", true),
+    ("0", false),
+    ("ON", true),
+    ("OFF", false),
+    ("TRUE", true),
+    ("FALSE", false),
+];
+
+impl From<bool> for Boolean {
+    fn from(value: bool) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Boolean> for bool {
+    fn from(value: Boolean) -> Self {
+        value.0
+    }
+}
+
+impl ScpiSerialize for Boolean {
+    fn serialize(&self, out: &mut String) {
+        out.push_str(if self.0 { "ON" } else { "OFF" });
+    }
+
+    fn serialized_len(&self) -> usize {
+        if self.0 { 2 } else { 3 }
+    }
+}
+
+impl ScpiDeserialize for Boolean {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        for (form, value) in BOOLEAN_FORMS {
+            let Some((token, rest)) = input.split_at_checked(form.len()) else {
+                continue;
+            };
+            if token.eq_ignore_ascii_case(form)
+                && !rest.starts_with(|c: char| c.is_ascii_alphanumeric())
+            {
+                *input = rest;
+                return Ok(Self(value));
+            }
+        }
+        Err(Error::ResponseDecoding(format!(
+            "Expected boolean (1, 0, ON, OFF, TRUE or FALSE), not matched `{input}`"
+        )))
+    }
+}
+
+impl DeserializedWithParse for char {
+    fn prefix_len(input: &str) -> usize {
+        input.chars().next().map_or(0, char::len_utf8)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{ScpiDeserialize, ScpiSerialize};
+    use super::*;
+    use crate::numeric::approx_eq;
 
     #[test]
     fn serialize_primitives() {
@@ -142,4 +378,237 @@ mod tests {
         assert_eq!(f32::deserialize_complete("0.00000002").unwrap(), 2e-8f32);
         assert_eq!(f64::deserialize_complete("-0.2").unwrap(), -0.2e0f64);
     }
+
+    #[test]
+    fn deserialize_overflow() {
+        assert!(matches!(
+            u16::deserialize_complete("99999999999"),
+            Err(Error::OutOfRange(_))
+        ));
+        assert!(matches!(
+            u8::deserialize_complete("256"),
+            Err(Error::OutOfRange(_))
+        ));
+        assert!(matches!(
+            i8::deserialize_complete("-129"),
+            Err(Error::OutOfRange(_))
+        ));
+        assert!(matches!(
+            i32::deserialize_complete("3000000000"),
+            Err(Error::OutOfRange(_))
+        ));
+
+        assert!(matches!(
+            u16::deserialize_complete("ON"),
+            Err(Error::ResponseDecoding(_))
+        ));
+        assert!(matches!(
+            i32::deserialize_complete("-"),
+            Err(Error::ResponseDecoding(_))
+        ));
+        assert_eq!(
+            u8::deserialize_complete("256").unwrap_err().to_string(),
+            "Value out of range: `256` exceeds the range of u8"
+        );
+    }
+
+    macro_rules! assert_roundtrip_bounds {
+        ($type:ty) => {
+            crate::assert_scpi_roundtrip!(<$type>::MIN);
+            crate::assert_scpi_roundtrip!(<$type>::MAX);
+            crate::assert_scpi_roundtrip!(<$type>::default());
+        };
+    }
+
+    #[test]
+    fn deserialize_float_forms() {
+        assert_eq!(f64::deserialize_complete("1.").unwrap(), 1.0);
+        assert_eq!(f64::deserialize_complete(".5").unwrap(), 0.5);
+        assert_eq!(f64::deserialize_complete("-.5").unwrap(), -0.5);
+        assert_eq!(f64::deserialize_complete("1.E3").unwrap(), 1e3);
+        assert_eq!(f32::deserialize_complete("+2.e-1").unwrap(), 0.2);
+        for invalid in ["+", "-", ".", "+.", "E3", "-E3"] {
+            assert!(f64::deserialize_complete(invalid).is_err(), "{invalid}");
+        }
+        assert_eq!(float_prefix("+,1"), "");
+        assert_eq!(float_prefix("1.,2"), "1.");
+    }
+
+    #[test]
+    fn deserialize_non_finite() {
+        let parse = |input| ExtendedFloat::deserialize_complete(input).unwrap().0;
+        assert!(parse("NAN").is_nan());
+        assert!(parse("-nan").is_nan());
+        assert_eq!(parse("INF"), f64::INFINITY);
+        assert_eq!(parse("-INF"), f64::NEG_INFINITY);
+        assert_eq!(parse("+Infinity"), f64::INFINITY);
+        assert_eq!(parse("-1.5E3"), -1.5e3);
+        assert_eq!(
+            ExtendedFloat(f64::NEG_INFINITY).serialize_to_string(),
+            "-INF"
+        );
+        assert_eq!(ExtendedFloat(f64::NAN).serialize_to_string(), "NAN");
+        crate::assert_scpi_roundtrip!(ExtendedFloat(f64::INFINITY));
+
+        let input = &mut "INF,1";
+        assert_eq!(ExtendedFloat::deserialize(input).unwrap().0, f64::INFINITY);
+        assert_eq!(*input, ",1");
+
+        for keyword in ["INFO", "NANO", "INF_1", "INFINITE"] {
+            let input = &mut &*keyword;
+            assert!(ExtendedFloat::deserialize(input).is_err(), "{keyword}");
+            assert_eq!(*input, keyword);
+        }
+        for non_finite in ["NAN", "INF", "-INF", "INFO"] {
+            assert!(
+                f64::deserialize_complete(non_finite).is_err(),
+                "{non_finite}"
+            );
+            assert!(
+                f32::deserialize_complete(non_finite).is_err(),
+                "{non_finite}"
+            );
+        }
+        assert!(u8::deserialize_complete("NAN").is_err());
+    }
+
+    #[test]
+    fn numeric_prefixes() {
+        assert_eq!(unsigned_prefix("12.5"), "12");
+        assert_eq!(unsigned_prefix("-12"), "");
+        assert_eq!(signed_prefix("-12.5"), "-12");
+        assert_eq!(signed_prefix("+3,4"), "+3");
+        assert_eq!(float_prefix("1.2.3"), "1.2");
+        assert_eq!(float_prefix("-1.5E+3V"), "-1.5E+3");
+        assert_eq!(float_prefix("ON"), "");
+        assert_eq!(f32::prefix_len("1.2.3"), 3);
+    }
+
+    macro_rules! assert_serialized_len {
+        ($($value:expr),* $(,)?) => {
+            $(
+                let value = $value;
+                assert_eq!(
+                    value.serialized_len(),
+                    value.serialize_to_string().len(),
+                    "{}",
+                    value.serialize_to_string()
+                );
+            )*
+        };
+    }
+
+    #[test]
+    fn serialized_len() {
+        assert_serialized_len!(0u8, 9u8, 10u8, u8::MAX, u64::MAX, u128::MAX, 1000u32);
+        assert_serialized_len!(0i8, -1i8, i8::MIN, i8::MAX, i64::MIN, i128::MIN, -10i32);
+        assert_serialized_len!(1.5f32, -1e-9f64, f64::NAN, 'µ', "VOLT", String::from("µA"));
+        assert_serialized_len!(
+            Boolean(true),
+            Boolean(false),
+            Some(12u8),
+            None::<u8>,
+            &-5i16
+        );
+    }
+
+    #[test]
+    fn roundtrip_primitives() {
+        for_numeric_primitives!(assert_roundtrip_bounds);
+        crate::assert_scpi_roundtrip!('x');
+        crate::assert_scpi_roundtrip!(f64::MIN_POSITIVE);
+        crate::assert_scpi_roundtrip!(-1.25e-9f32);
+    }
+
+    #[test]
+    fn roundtrip_floats() {
+        for value in [0.1f64, -1.25e-9, 6.02e23, 1.0 / 3.0] {
+            let result = f64::deserialize_complete(&value.serialize_to_string()).unwrap();
+            assert!(approx_eq(result, value, 1e-12));
+        }
+    }
+
+    #[test]
+    fn serialize_string() {
+        assert_eq!("DATA.CSV".serialize_to_string(), "DATA.CSV");
+        assert_eq!(String::from("a b").serialize_to_string(), "a b");
+    }
+
+    #[test]
+    fn boolean() {
+        assert_eq!(Boolean(true).serialize_to_string(), "ON");
+        assert_eq!(Boolean(false).serialize_to_string(), "OFF");
+        let table = [
+            ("1", true),
+            ("0", false),
+            ("ON", true),
+            ("OFF", false),
+            ("on", true),
+            ("Off", false),
+            ("TRUE", true),
+            ("FALSE", false),
+            ("true", true),
+            ("False", false),
+        ];
+        for (response, expected) in table {
+            assert_eq!(
+                Boolean::deserialize_complete(response).unwrap(),
+                Boolean(expected),
+                "{response}"
+            );
+        }
+        for invalid in ["10", "ONCE", "OUTP", "TRUEST", "YES", "2", "", "T"] {
+            let error = Boolean::deserialize_complete(invalid).unwrap_err();
+            assert!(error.to_string().contains("Expected boolean"), "{invalid}");
+        }
+        crate::assert_scpi_roundtrip!(Boolean(true));
+        crate::assert_scpi_roundtrip!(Boolean(false));
+    }
+
+    #[test]
+    fn boolean_composition() {
+        let input = &mut "ON;VOLT 5.0";
+        assert_eq!(Boolean::deserialize(input).unwrap(), Boolean(true));
+        assert!(crate::match_literal(input, ";VOLT ").is_ok());
+        assert_eq!(f32::deserialize(input).unwrap(), 5.0);
+        assert!(input.is_empty());
+
+        let input = &mut "0,OFF";
+        assert_eq!(Boolean::deserialize(input).unwrap(), Boolean(false));
+        assert!(crate::match_literal(input, ",").is_ok());
+        assert_eq!(Boolean::deserialize(input).unwrap(), Boolean(false));
+
+        let input = &mut "OFFS";
+        assert!(Boolean::deserialize(input).is_err());
+        assert_eq!(*input, "OFFS");
+    }
+
+    #[test]
+    fn deserialize_string() {
+        assert_eq!(String::deserialize_complete("a,b c").unwrap(), "a,b c");
+        assert_eq!(String::deserialize_complete("").unwrap(), "");
+
+        let input = &mut "12,rest";
+        u8::deserialize(input).unwrap();
+        crate::match_literal(input, ",").unwrap();
+        assert_eq!(String::deserialize(input).unwrap(), "rest");
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn serialize_char() {
+        assert_eq!('A'.serialize_to_string(), "A");
+        assert_eq!('µ'.serialize_to_string(), "µ");
+    }
+
+    #[test]
+    fn deserialize_char() {
+        assert_eq!(char::deserialize_complete("A").unwrap(), 'A');
+        assert_eq!(char::deserialize_complete("µ").unwrap(), 'µ');
+        assert!(char::deserialize_complete("").is_err());
+
+        let input = &mut "AB";
+        assert_eq!(char::deserialize(input).unwrap(), 'A');
+        assert_eq!(*input, "B");
+    }
 }
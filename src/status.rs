@@ -0,0 +1,260 @@
+use std::ops::{BitAnd, BitOr};
+
+use crate::numeric::SignedExplicit;
+use crate::{
+    Result, ScpiDeserialize, ScpiSerialize, impl_scpi_request, impl_scpi_serialize,
+    scpi_command_pair,
+};
+
+macro_rules! status_register {
+    (
+        $(#[$meta:meta])*
+        $name:ident($bits:ty) {
+            $(
+                $(#[$flag_meta:meta])*
+                $flag:ident = $bit:literal;
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+        pub struct $name(pub $bits);
+
+        impl $name {
+            $(
+                $(#[$flag_meta])*
+                pub const $flag: Self = Self(1 << $bit);
+            )*
+
+            pub const fn empty() -> Self {
+                Self(0)
+            }
+
+            pub const fn bits(self) -> $bits {
+                self.0
+            }
+
+            /// Whether all bits of `other` are set.
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            pub const fn intersects(self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+        }
+
+        impl BitOr for $name {
+            type Output = Self;
+
+            fn bitor(self, rhs: Self) -> Self {
+                Self(self.0 | rhs.0)
+            }
+        }
+
+        impl BitAnd for $name {
+            type Output = Self;
+
+            fn bitand(self, rhs: Self) -> Self {
+                Self(self.0 & rhs.0)
+            }
+        }
+
+        impl ScpiSerialize for $name {
+            fn serialize(&self, out: &mut String) {
+                self.0.serialize(out);
+            }
+        }
+
+        impl ScpiDeserialize for $name {
+            fn deserialize(input: &mut &str) -> Result<Self> {
+                SignedExplicit::<$bits>::deserialize(input).map(|value| Self(value.0))
+            }
+        }
+    };
+}
+
+status_register!(
+    /// Status byte register (IEEE 488.2 and SCPI), read by `*STB?` or a serial poll.
+    StatusByte(u8) {
+        /// Error/event queue is not empty.
+        ERROR_QUEUE = 2;
+        /// Summary of the questionable status register.
+        QUESTIONABLE = 3;
+        /// Message available in the output queue.
+        MESSAGE_AVAILABLE = 4;
+        /// Summary of the standard event status register.
+        EVENT_STATUS = 5;
+        /// Request service, respectively master summary status for `*STB?`.
+        REQUEST_SERVICE = 6;
+        /// Summary of the operation status register.
+        OPERATION = 7;
+    }
+);
+
+status_register!(
+    /// Standard event status register (IEEE 488.2), read and cleared by `*ESR?`.
+    StandardEventStatus(u8) {
+        /// All pending operations completed after `*OPC`.
+        OPERATION_COMPLETE = 0;
+        REQUEST_CONTROL = 1;
+        QUERY_ERROR = 2;
+        DEVICE_ERROR = 3;
+        EXECUTION_ERROR = 4;
+        COMMAND_ERROR = 5;
+        USER_REQUEST = 6;
+        POWER_ON = 7;
+    }
+);
+
+/// Query the status byte, `*STB?`.
+pub struct StatusByteQuery;
+impl_scpi_serialize!(StatusByteQuery, ["*STB?"]);
+impl_scpi_request!(StatusByteQuery, StatusByte);
+
+/// Query and clear the standard event status register, `*ESR?`.
+pub struct StandardEventStatusQuery;
+impl_scpi_serialize!(StandardEventStatusQuery, ["*ESR?"]);
+impl_scpi_request!(StandardEventStatusQuery, StandardEventStatus);
+
+status_register!(
+    /// Operation status register (SCPI), bits 8 to 12 are instrument specific.
+    OperationStatus(u16) {
+        CALIBRATING = 0;
+        /// Waiting for a signal to stabilize.
+        SETTLING = 1;
+        /// Changing a range.
+        RANGING = 2;
+        /// Sweep in progress.
+        SWEEPING = 3;
+        /// Measurement in progress.
+        MEASURING = 4;
+        /// Waiting for a trigger event.
+        WAITING_FOR_TRIGGER = 5;
+        /// Waiting for an arm event.
+        WAITING_FOR_ARM = 6;
+        /// Correction in progress.
+        CORRECTING = 7;
+        /// Summary of the operation status of an instrument in a multi-instrument device.
+        INSTRUMENT_SUMMARY = 13;
+        /// A user defined program is running.
+        PROGRAM_RUNNING = 14;
+    }
+);
+
+status_register!(
+    /// Questionable status register (SCPI), indicating the quality of the signal or measured
+    /// values, bits 9 to 12 are instrument specific.
+    QuestionableStatus(u16) {
+        VOLTAGE = 0;
+        CURRENT = 1;
+        TIME = 2;
+        POWER = 3;
+        TEMPERATURE = 4;
+        FREQUENCY = 5;
+        PHASE = 6;
+        MODULATION = 7;
+        CALIBRATION = 8;
+        /// Summary of the questionable status of an instrument in a multi-instrument device.
+        INSTRUMENT_SUMMARY = 13;
+        /// A command was executed, but produced a questionable result.
+        COMMAND_WARNING = 14;
+    }
+);
+
+/// Query the operation condition register, `STATus:OPERation:CONDition?`, reading does not clear it.
+pub struct OperationConditionQuery;
+impl_scpi_serialize!(OperationConditionQuery, ["STAT:OPER:COND?"]);
+impl_scpi_request!(OperationConditionQuery, OperationStatus);
+
+/// Query and clear the operation event register, `STATus:OPERation:EVENt?`.
+pub struct OperationEventQuery;
+impl_scpi_serialize!(OperationEventQuery, ["STAT:OPER:EVEN?"]);
+impl_scpi_request!(OperationEventQuery, OperationStatus);
+
+scpi_command_pair!(
+    /// Operation enable register, `STATus:OPERation:ENABle`, selecting the events summarized in
+    /// [`StatusByte::OPERATION`].
+    pub struct OperationEnable, OperationEnableQuery => "STAT:OPER:ENAB", OperationStatus
+);
+
+/// Query the questionable condition register, `STATus:QUEStionable:CONDition?`, reading does not clear it.
+pub struct QuestionableConditionQuery;
+impl_scpi_serialize!(QuestionableConditionQuery, ["STAT:QUES:COND?"]);
+impl_scpi_request!(QuestionableConditionQuery, QuestionableStatus);
+
+/// Query and clear the questionable event register, `STATus:QUEStionable:EVENt?`.
+pub struct QuestionableEventQuery;
+impl_scpi_serialize!(QuestionableEventQuery, ["STAT:QUES:EVEN?"]);
+impl_scpi_request!(QuestionableEventQuery, QuestionableStatus);
+
+scpi_command_pair!(
+    /// Questionable enable register, `STATus:QUEStionable:ENABle`, selecting the events summarized in
+    /// [`StatusByte::QUESTIONABLE`].
+    pub struct QuestionableEnable, QuestionableEnableQuery => "STAT:QUES:ENAB", QuestionableStatus
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_byte() {
+        let status = StatusByte::deserialize_complete("68").unwrap();
+        assert_eq!(
+            status,
+            StatusByte::REQUEST_SERVICE | StatusByte::ERROR_QUEUE
+        );
+        assert!(status.contains(StatusByte::REQUEST_SERVICE));
+        assert!(!status.contains(StatusByte::REQUEST_SERVICE | StatusByte::OPERATION));
+        assert!(status.intersects(StatusByte::REQUEST_SERVICE | StatusByte::OPERATION));
+        assert_eq!(status.serialize_to_string(), "68");
+
+        assert_eq!(
+            StatusByte::deserialize_complete("+16").unwrap(),
+            StatusByte::MESSAGE_AVAILABLE
+        );
+        assert!(StatusByte::deserialize_complete("256").is_err());
+        assert_eq!(StatusByte::empty().bits(), 0);
+    }
+
+    #[test]
+    fn standard_event_status() {
+        let status = StandardEventStatus::deserialize_complete("+33").unwrap();
+        assert_eq!(
+            status,
+            StandardEventStatus::COMMAND_ERROR | StandardEventStatus::OPERATION_COMPLETE
+        );
+        assert_eq!(StandardEventStatusQuery.serialize_to_string(), "*ESR?");
+    }
+
+    #[test]
+    fn operation_and_questionable_status() {
+        let status = OperationStatus::deserialize_complete("+16432").unwrap();
+        assert_eq!(
+            status,
+            OperationStatus::PROGRAM_RUNNING
+                | OperationStatus::WAITING_FOR_TRIGGER
+                | OperationStatus::MEASURING
+        );
+        assert!(OperationStatus::deserialize_complete("65536").is_err());
+        assert_eq!(
+            OperationConditionQuery.serialize_to_string(),
+            "STAT:OPER:COND?"
+        );
+
+        let status = QuestionableStatus::deserialize_complete("3").unwrap();
+        assert!(status.contains(QuestionableStatus::VOLTAGE | QuestionableStatus::CURRENT));
+        assert_eq!(
+            QuestionableEventQuery.serialize_to_string(),
+            "STAT:QUES:EVEN?"
+        );
+        assert_eq!(
+            QuestionableEnable {
+                value: QuestionableStatus::TEMPERATURE
+            }
+            .serialize_to_string(),
+            "STAT:QUES:ENAB 16"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{Error, Result, ScpiDeserialize, ScpiSerialize};
+
+/// Characters which cannot be part of bare text without being misparsed.
+pub const RESERVED_CHARACTERS: &[char] = &[',', ';', '"', '\'', '\n', '\r'];
+
+/// Quoted string data, e.g. `"Hello ""World"""`.
+///
+/// Serialization wraps the text in double quotes and doubles embedded quotes,
+/// so any text can be transmitted safely. Deserialization accepts double and single quotes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ScpiString(pub String);
+
+impl From<&str> for ScpiString {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl From<String> for ScpiString {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl ScpiSerialize for ScpiString {
+    fn serialize(&self, out: &mut String) {
+        out.push('"');
+        out.push_str(&self.0.replace('"', "\"\""));
+        out.push('"');
+    }
+}
+
+impl ScpiDeserialize for ScpiString {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let quote = match input.chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => {
+                return Err(Error::ResponseDecoding(format!(
+                    "Expected quoted string, not matched `{input}`"
+                )));
+            }
+        };
+
+        let mut result = String::new();
+        let mut rest = &input[1..];
+        loop {
+            let Some(index) = rest.find(quote) else {
+                return Err(Error::ResponseDecoding(format!(
+                    "Missing closing quote in `{input}`"
+                )));
+            };
+            result.push_str(&rest[..index]);
+            rest = &rest[index + 1..];
+            match rest.strip_prefix(quote) {
+                Some(escaped) => {
+                    result.push(quote);
+                    rest = escaped;
+                }
+                None => break,
+            }
+        }
+        *input = rest;
+        Ok(Self(result))
+    }
+}
+
+/// Bare (unquoted) text validated to contain none of the [`RESERVED_CHARACTERS`],
+/// which would otherwise silently corrupt the command. Use [`ScpiString`] for arbitrary text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BareString(String);
+
+impl BareString {
+    pub fn new(value: impl Into<String>) -> Result<Self> {
+        let value = value.into();
+        if value.contains(RESERVED_CHARACTERS) {
+            return Err(Error::InvalidParameter(format!(
+                "Bare text `{}` contains reserved characters",
+                value.escape_debug()
+            )));
+        }
+        Ok(Self(value))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl ScpiSerialize for BareString {
+    fn serialize(&self, out: &mut String) {
+        out.push_str(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_string() {
+        assert_eq!(ScpiString::from("abc").serialize_to_string(), "\"abc\"");
+        assert_eq!(
+            ScpiString::from("a \"b\", c;").serialize_to_string(),
+            "\"a \"\"b\"\", c;\""
+        );
+        assert_eq!(ScpiString::default().serialize_to_string(), "\"\"");
+    }
+
+    #[test]
+    fn deserialize_string() {
+        assert_eq!(
+            ScpiString::deserialize_complete("\"abc\"").unwrap(),
+            "abc".into()
+        );
+        assert_eq!(
+            ScpiString::deserialize_complete("\"a \"\"b\"\", c\"").unwrap(),
+            "a \"b\", c".into()
+        );
+        assert_eq!(
+            ScpiString::deserialize_complete("'it''s \"x\"'").unwrap(),
+            "it's \"x\"".into()
+        );
+        assert_eq!(ScpiString::deserialize_complete("\"\"").unwrap(), "".into());
+        assert!(ScpiString::deserialize_complete("abc").is_err());
+        assert!(ScpiString::deserialize_complete("\"abc").is_err());
+
+        let input = &mut "\"a,b\",1";
+        assert_eq!(ScpiString::deserialize(input).unwrap(), "a,b".into());
+        assert_eq!(*input, ",1");
+    }
+
+    #[test]
+    fn bare_string() {
+        let text = BareString::new("DATA.CSV").unwrap();
+        assert_eq!(text.serialize_to_string(), "DATA.CSV");
+        assert_eq!(text.as_str(), "DATA.CSV");
+        assert!(BareString::new("a,b").is_err());
+        assert!(BareString::new("a;b").is_err());
+        assert!(BareString::new("a\nb").is_err());
+        assert!(BareString::new("\"a\"").is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::block::{Endianness, decode_real_block, decode_real64_block, read_block};
+use crate::{Error, Result, ScpiDeserialize, ScpiSerialize};
+
+/// Format of trace data as configured by `FORMat:DATA`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    /// Comma separated numbers, `ASCii`.
+    Ascii,
+    /// Definite length block of 32 bit floats, `REAL,32`.
+    Real32(Endianness),
+    /// Definite length block of 64 bit floats, `REAL,64`.
+    Real64(Endianness),
+}
+
+/// Serializes the `FORMat:DATA` parameter, the byte order is configured separately by `FORMat:BORDer`.
+impl ScpiSerialize for DataFormat {
+    fn serialize(&self, out: &mut String) {
+        out.push_str(match self {
+            DataFormat::Ascii => "ASC",
+            DataFormat::Real32(_) => "REAL,32",
+            DataFormat::Real64(_) => "REAL,64",
+        });
+    }
+}
+
+/// Trace values, e.g. of a `:CURVe?` or `:TRACe?` query, independent of the transfer format.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TraceData(pub Vec<f64>);
+
+impl TraceData {
+    /// Decode a raw response (without terminator) in the given format.
+    pub fn decode(response: &[u8], format: DataFormat) -> Result<Self> {
+        let values = match format {
+            DataFormat::Ascii => {
+                let response = std::str::from_utf8(response).map_err(|e| {
+                    Error::ResponseDecoding(format!("Response is not valid UTF-8: {e}"))
+                })?;
+                Vec::<f64>::deserialize_complete_trimmed(response)?
+            }
+            DataFormat::Real32(endian) => decode_real_block(block_payload(response)?, endian)?
+                .into_iter()
+                .map(f64::from)
+                .collect(),
+            DataFormat::Real64(endian) => decode_real64_block(block_payload(response)?, endian)?,
+        };
+        Ok(Self(values))
+    }
+}
+
+fn block_payload(response: &[u8]) -> Result<&[u8]> {
+    let mut cursor = response;
+    let payload = read_block(&mut cursor, true)?;
+    if !cursor.is_empty() {
+        return Err(Error::ResponseDecoding(format!(
+            "Unexpected {} bytes following the block",
+            cursor.len()
+        )));
+    }
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(payload: &[u8]) -> Vec<u8> {
+        let length = payload.len().to_string();
+        let mut block = format!("#{}{length}", length.len()).into_bytes();
+        block.extend_from_slice(payload);
+        block
+    }
+
+    #[test]
+    fn decode_formats() {
+        let expected = TraceData(vec![1.5, -0.25, 1000.0]);
+
+        let ascii = TraceData::decode(b"1.5,-0.25,1E3", DataFormat::Ascii).unwrap();
+        assert_eq!(ascii, expected);
+
+        let payload: Vec<u8> = [1.5f32, -0.25, 1000.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let real32 =
+            TraceData::decode(&block(&payload), DataFormat::Real32(Endianness::Little)).unwrap();
+        assert_eq!(real32, expected);
+
+        let payload: Vec<u8> = [1.5f64, -0.25, 1000.0]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect();
+        let real64 =
+            TraceData::decode(&block(&payload), DataFormat::Real64(Endianness::Big)).unwrap();
+        assert_eq!(real64, expected);
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert!(TraceData::decode(b"1.5,x", DataFormat::Ascii).is_err());
+        assert!(TraceData::decode(b"1.5", DataFormat::Real32(Endianness::Little)).is_err());
+        assert!(TraceData::decode(b"#18abcd", DataFormat::Real32(Endianness::Little)).is_err());
+        assert!(TraceData::decode(b"#13abc", DataFormat::Real32(Endianness::Little)).is_err());
+    }
+
+    #[test]
+    fn serialize_format() {
+        assert_eq!(DataFormat::Ascii.serialize_to_string(), "ASC");
+        assert_eq!(
+            DataFormat::Real32(Endianness::Big).serialize_to_string(),
+            "REAL,32"
+        );
+    }
+}
@@ -0,0 +1,498 @@
+use std::fmt;
+use std::net::ToSocketAddrs;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::batch::{CommandBatch, CommandScript, QueryBatch};
+use crate::commands::{EventStatusEnable, SelfTest, SelfTestResult, SystemErrorQuery};
+use crate::containers::Separated;
+use crate::status::{StandardEventStatus, StandardEventStatusQuery, StatusByte, StatusByteQuery};
+use crate::trace::{DataFormat, TraceData};
+use crate::{Error, Result, ScpiDeserialize, ScpiRequest, ScpiSerialize};
+
+mod shared;
+mod tcp;
+mod usbtmc;
+mod visa;
+mod vxi11;
+
+pub use shared::SharedInstrument;
+pub use tcp::TcpInstrument;
+pub use usbtmc::{BulkTransfer, UsbtmcInstrument};
+pub use visa::{Resource, VisaInstrument};
+pub use vxi11::Vxi11Instrument;
+
+/// Message terminator appended to sent messages and expected after received messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Terminator {
+    #[default]
+    Lf,
+    Cr,
+    CrLf,
+}
+
+impl Terminator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Terminator::Lf => "\n",
+            Terminator::Cr => "\r",
+            Terminator::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Detection of the end of a received message.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Framing {
+    /// Messages end with the configured [`Terminator`].
+    #[default]
+    Terminator,
+    /// Messages end with a prompt, e.g. `SCPI> ` of instruments in an interactive mode.
+    /// The prompt and a terminator directly in front of it are discarded.
+    Prompt(String),
+}
+
+/// Raw bytes exchanged with an instrument, see [`InstrumentBuilder::on_traffic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Traffic<'a> {
+    /// Data written, including the terminator.
+    Sent(&'a [u8]),
+    /// Data as read from the stream, which may be split or combined arbitrarily.
+    Received(&'a [u8]),
+    /// The connection was lost and re-established, see [`InstrumentBuilder::reconnect`].
+    Reconnected,
+    /// Stale data discarded by [`Connection::flush_input`], possibly received before.
+    Discarded(&'a [u8]),
+}
+
+#[derive(Clone)]
+pub(crate) struct TrafficHook(Arc<dyn Fn(Traffic<'_>) + Send + Sync>);
+
+impl TrafficHook {
+    pub(crate) fn call(&self, traffic: Traffic<'_>) {
+        (self.0)(traffic)
+    }
+}
+
+impl fmt::Debug for TrafficHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TrafficHook")
+    }
+}
+
+/// Message based communication with an instrument.
+///
+/// Implementers provide the transmission of raw messages,
+/// the typed request handling is built on top.
+pub trait Connection {
+    /// Transmit a single message, the terminator is appended by the connection.
+    fn send(&mut self, message: &str) -> Result<()>;
+
+    /// Receive a single response message as raw bytes, without its terminator.
+    /// Terminators within double quoted strings do not end the message.
+    /// Definite length blocks (`#<n><length><data>`) are received completely,
+    /// even if their payload contains the terminator.
+    fn receive_bytes(&mut self) -> Result<Vec<u8>>;
+
+    /// Receive a single response message, without its terminator.
+    fn receive(&mut self) -> Result<String> {
+        String::from_utf8(self.receive_bytes()?)
+            .map_err(|e| Error::ResponseDecoding(format!("Response is not valid UTF-8: {e}")))
+    }
+
+    /// Discard any pending received data without waiting for more, e.g. stale bytes
+    /// of an earlier response after a desynchronization. Discarded data is reported as
+    /// [`Traffic::Discarded`]. The default does nothing, for transports without
+    /// their own input buffer.
+    fn flush_input(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Timeout applied to each read and write.
+    fn timeout(&self) -> Duration;
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<()>;
+
+    /// Send a raw command string, the escape hatch for commands not modeled as types.
+    fn write_str(&mut self, command: &str) -> Result<()> {
+        self.send(command)
+    }
+
+    /// Send a raw query string and return the raw response, without its terminator.
+    fn query_str(&mut self, query: &str) -> Result<String> {
+        self.send(query)?;
+        self.receive()
+    }
+
+    /// Send a command without waiting for a response.
+    fn execute<C: ScpiSerialize + ?Sized>(&mut self, command: &C) -> Result<()> {
+        self.send(&command.serialize_to_string())
+    }
+
+    /// Send a compound command, see [`CommandBatch`]. Same as [`Connection::execute`],
+    /// unless [`InstrumentBuilder::clear_status_before_batch`] is enabled.
+    fn execute_batch(&mut self, batch: &CommandBatch<'_>) -> Result<()> {
+        self.execute(batch)
+    }
+
+    /// Send a multi-line script in one write, see [`CommandScript`].
+    /// Connections separate the lines with their terminator, the default uses `\n`.
+    fn execute_script(&mut self, script: &CommandScript<'_>) -> Result<()> {
+        self.send(&script.serialize_to_string())
+    }
+
+    /// Send a request and deserialize its response.
+    fn query<R: ScpiRequest + ?Sized>(&mut self, request: &R) -> Result<R::Response>
+    where
+        R::Response: ScpiDeserialize,
+    {
+        self.execute(request)?;
+        let response = self.receive()?;
+        R::Response::deserialize_complete(&response)
+    }
+
+    /// Send a tuple of queries in one message, e.g. `VOLT?;CURR?`,
+    /// and deserialize the `;` separated responses into a tuple, see [`QueryBatch`].
+    fn query_batch<T, R>(&mut self, queries: T) -> Result<R>
+    where
+        QueryBatch<T>: ScpiRequest<Response = Separated<R, ';'>>,
+        Separated<R, ';'>: ScpiDeserialize,
+    {
+        self.query(&QueryBatch(queries))
+            .map(|responses| responses.0)
+    }
+
+    /// Send a trace query, e.g. `:CURVe?`, and decode the response in the
+    /// format currently configured on the instrument by `FORMat:DATA`.
+    fn query_trace<R: ScpiSerialize + ?Sized>(
+        &mut self,
+        request: &R,
+        format: DataFormat,
+    ) -> Result<Vec<f64>> {
+        self.execute(request)?;
+        let response = self.receive_bytes()?;
+        TraceData::decode(&response, format).map(|trace| trace.0)
+    }
+
+    /// Read the status byte, e.g. for service request driven workflows.
+    ///
+    /// Transports capable of a hardware serial poll, like [`Vxi11Instrument`], override this,
+    /// which works even while the instrument is busy. The default, used by [`TcpInstrument`]
+    /// and [`UsbtmcInstrument`], falls back to querying `*STB?`.
+    fn serial_poll(&mut self) -> Result<StatusByte> {
+        self.query(&StatusByteQuery)
+    }
+
+    /// Send a command and wait for the instrument to complete it.
+    ///
+    /// The operation complete event is enabled in the event status enable register
+    /// with `*ESE 1`, pending events are cleared by reading `*ESR?`, and the command
+    /// is followed by `*OPC`. The status byte is then polled until its event status bit
+    /// is set, failing with [`Error::Timeout`] if this does not happen within `timeout`.
+    fn run_to_completion<C: ScpiSerialize + ?Sized>(
+        &mut self,
+        command: &C,
+        timeout: Duration,
+    ) -> Result<()> {
+        const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+        self.execute(&EventStatusEnable {
+            value: StandardEventStatus::OPERATION_COMPLETE,
+        })?;
+        self.query(&StandardEventStatusQuery)?;
+        self.execute(command)?;
+        self.write_str("*OPC")?;
+
+        let start = Instant::now();
+        while !self.serial_poll()?.contains(StatusByte::EVENT_STATUS) {
+            if start.elapsed() >= timeout {
+                return Err(Error::Timeout { after: timeout });
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        self.query(&StandardEventStatusQuery)?;
+        Ok(())
+    }
+
+    /// Run the instrument self-test with `*TST?`, allowing it 60 s to complete.
+    fn self_test(&mut self) -> Result<SelfTestResult> {
+        self.query_with_timeout(&SelfTest, Duration::from_secs(60))
+    }
+
+    /// Drain the error queue with `SYSTem:ERRor?` and fail with [`Error::Instrument`]
+    /// listing all queued errors, if any. At most 100 entries are read, in case
+    /// an instrument never reports an empty queue.
+    fn assert_no_errors(&mut self) -> Result<()> {
+        const MAX_ENTRIES: usize = 100;
+
+        let mut errors = Vec::new();
+        for _ in 0..MAX_ENTRIES {
+            let entry = self.query(&SystemErrorQuery)?;
+            if !entry.is_error() {
+                break;
+            }
+            errors.push(entry);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Instrument(errors))
+        }
+    }
+
+    /// Send a command and check the error queue afterwards, see [`Connection::assert_no_errors`].
+    /// This catches malformed commands immediately, at the cost of an additional query.
+    fn execute_checked<C: ScpiSerialize + ?Sized>(&mut self, command: &C) -> Result<()> {
+        self.execute(command)?;
+        self.assert_no_errors()
+    }
+
+    /// Same as [`Connection::query`], but overrides the timeout for this request only,
+    /// e.g. for a slow `*TST?`. The previous timeout is restored afterwards, even on error.
+    fn query_with_timeout<R: ScpiRequest + ?Sized>(
+        &mut self,
+        request: &R,
+        timeout: Duration,
+    ) -> Result<R::Response>
+    where
+        R::Response: ScpiDeserialize,
+    {
+        let previous = self.timeout();
+        self.set_timeout(timeout)?;
+        let result = self.query(request);
+        let restored = self.set_timeout(previous);
+        let response = result?;
+        restored?;
+        Ok(response)
+    }
+}
+
+/// Configuration of an instrument connection.
+///
+/// Defaults follow common SCPI practice, a timeout of 5 s and `\n` as terminator.
+#[derive(Debug, Clone)]
+pub struct InstrumentBuilder {
+    timeout: Duration,
+    terminator: Terminator,
+    framing: Framing,
+    read_buffer_size: usize,
+    max_response_bytes: usize,
+    echo: bool,
+    reconnect: bool,
+    block_terminator: bool,
+    dry_run: bool,
+    clear_status_before_batch: bool,
+    flush_before_query: bool,
+    inter_command_delay: Duration,
+    empty_response_error: bool,
+    on_traffic: Option<TrafficHook>,
+}
+
+impl Default for InstrumentBuilder {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            terminator: Terminator::default(),
+            framing: Framing::default(),
+            read_buffer_size: 4096,
+            max_response_bytes: 16 * 1024 * 1024,
+            echo: false,
+            reconnect: false,
+            block_terminator: true,
+            dry_run: false,
+            clear_status_before_batch: false,
+            flush_before_query: false,
+            inter_command_delay: Duration::ZERO,
+            empty_response_error: false,
+            on_traffic: None,
+        }
+    }
+}
+
+impl InstrumentBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Timeout for establishing the connection and each read and write.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn terminator(mut self, terminator: Terminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// Framing of received messages, sent messages are always terminated by the terminator.
+    pub fn framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Size of the chunks read from the underlying stream.
+    pub fn read_buffer_size(mut self, size: usize) -> Self {
+        self.read_buffer_size = size.max(1);
+        self
+    }
+
+    /// Maximum size of a single response, 16 MiB by default. Exceeding it fails with
+    /// [`Error::ResponseTooLarge`] instead of buffering without bounds, e.g. if a wrongly
+    /// configured terminator is never found. The data received so far is discarded.
+    pub fn max_response_bytes(mut self, max: usize) -> Self {
+        self.max_response_bytes = max;
+        self
+    }
+
+    /// Expect the instrument to echo each sent message before its response, as some
+    /// serial devices do. The echo is discarded and must match the sent message exactly,
+    /// otherwise [`Error::Transport`] reports the desynchronization. Each line of a
+    /// [`CommandScript`] is expected to be echoed on its own.
+    pub fn echo(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+
+    /// Transparently reconnect to the same address if a query fails because the connection
+    /// was reset or closed, and retry the query once. Commands are never retried, as they
+    /// may change the instrument state, their error is returned and a following query reconnects.
+    /// Compound queries changing the state, e.g. `VOLT 5;*OPC?`, must not be used with this option.
+    pub fn reconnect(mut self, reconnect: bool) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Expect the terminator after definite length blocks, enabled by default.
+    /// Disable it for instruments ending block responses with the payload, relying on
+    /// a hardware end of message (EOI, EOM) instead. A message then ends directly after
+    /// the declared number of payload bytes, leaving any following bytes to the next message.
+    /// This applies to [`Framing::Terminator`] only.
+    pub fn block_terminator(mut self, block_terminator: bool) -> Self {
+        self.block_terminator = block_terminator;
+        self
+    }
+
+    /// Serialize messages and pass them to the [`InstrumentBuilder::on_traffic`] hook,
+    /// but do not write them, e.g. to preview disruptive commands. The connection is still
+    /// established. Nothing is read either, each response is empty: [`Connection::query_str`]
+    /// returns `""` and typed queries fail to deserialize unless their response accepts empty input.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Prepend `*CLS` to every batch sent by [`Connection::execute_batch`] or
+    /// [`Connection::query_batch`] and every script sent by [`Connection::execute_script`],
+    /// so the error queue and event registers reflect only that batch, e.g. for
+    /// [`Connection::assert_no_errors`] afterwards.
+    /// This adds a command to each batch and discards errors of earlier commands.
+    pub fn clear_status_before_batch(mut self, clear: bool) -> Self {
+        self.clear_status_before_batch = clear;
+        self
+    }
+
+    /// Call [`Connection::flush_input`] before each query, so stale data never ends up in
+    /// a response, at the cost of an additional read without waiting per query.
+    pub fn flush_before_query(mut self, flush: bool) -> Self {
+        self.flush_before_query = flush;
+        self
+    }
+
+    /// Sleep for `delay` after each write, before anything else is read or written,
+    /// for slow instruments dropping responses to queries following a command too quickly.
+    /// This trades throughput for reliability, every message takes at least `delay`.
+    /// Zero by default.
+    pub fn inter_command_delay(mut self, delay: Duration) -> Self {
+        self.inter_command_delay = delay;
+        self
+    }
+
+    /// Fail typed queries with [`Error::EmptyResponse`] if the response is completely empty,
+    /// e.g. because an earlier error made the instrument discard the query, instead of
+    /// deserializing `""` with a confusing message. Responses accepting empty input,
+    /// e.g. an `Option`, can then no longer be empty. Raw [`Connection::query_str`] is unaffected.
+    pub fn empty_response_error(mut self, enabled: bool) -> Self {
+        self.empty_response_error = enabled;
+        self
+    }
+
+    /// Install a hook observing all raw bytes sent and received, e.g. for a hexdump.
+    /// This includes data never reaching the deserializer, like truncated responses.
+    pub fn on_traffic(mut self, hook: impl Fn(Traffic<'_>) + Send + Sync + 'static) -> Self {
+        self.on_traffic = Some(TrafficHook(Arc::new(hook)));
+        self
+    }
+
+    /// Deserialize the response of a typed query, see [`InstrumentBuilder::empty_response_error`].
+    pub(crate) fn decode_response<T: ScpiDeserialize>(&self, response: &str) -> Result<T> {
+        if self.empty_response_error && response.is_empty() {
+            return Err(Error::EmptyResponse);
+        }
+        T::deserialize_complete(response)
+    }
+
+    /// Message of a batch, a [`CommandBatch`] or [`QueryBatch`],
+    /// with a leading `*CLS` if configured.
+    pub(crate) fn batch_message(&self, batch: &(impl ScpiSerialize + ?Sized)) -> String {
+        let body = batch.serialize_to_string();
+        if !self.clear_status_before_batch {
+            return body;
+        }
+        if body.is_empty() {
+            "*CLS".to_string()
+        } else {
+            format!("*CLS;{body}")
+        }
+    }
+
+    /// Message of a script with the lines separated by the terminator,
+    /// with a leading `*CLS` line if configured.
+    pub(crate) fn script_message(&self, script: &CommandScript<'_>) -> String {
+        let terminator = self.terminator.as_str();
+        let mut message = String::new();
+        if self.clear_status_before_batch {
+            message.push_str("*CLS");
+            if !script.is_empty() {
+                message.push_str(terminator);
+            }
+        }
+        script.serialize_with_terminator(terminator, &mut message);
+        message
+    }
+
+    /// Connect to an instrument providing a raw SCPI socket, commonly on port 5025.
+    pub fn connect(self, address: impl ToSocketAddrs) -> Result<TcpInstrument> {
+        TcpInstrument::connect_with(address, self)
+    }
+
+    /// Connect to a device, e.g. `inst0`, via VXI-11, the core channel is looked up
+    /// with the portmapper of `host`. The read buffer size is the size requested per read.
+    ///
+    /// VXI-11 frames messages itself, so [`InstrumentBuilder::framing`] and
+    /// [`InstrumentBuilder::block_terminator`] do not apply. [`InstrumentBuilder::echo`],
+    /// [`InstrumentBuilder::reconnect`] and [`InstrumentBuilder::flush_before_query`]
+    /// are not supported either and ignored.
+    pub fn connect_vxi11(self, host: &str, device: &str) -> Result<Vxi11Instrument> {
+        Vxi11Instrument::connect_with(host, device, self)
+    }
+
+    /// Connect to an instrument addressed by a VISA resource string,
+    /// e.g. `TCPIP0::192.168.1.5::inst0::INSTR`, see [`VisaInstrument`].
+    pub fn connect_visa(self, resource: &str) -> Result<VisaInstrument> {
+        VisaInstrument::connect_with(resource, self)
+    }
+
+    /// Communicate via USBTMC over the bulk endpoints of an opened device,
+    /// see [`UsbtmcInstrument`]. The read buffer size is the size requested per transfer.
+    ///
+    /// USBTMC frames messages itself, so [`InstrumentBuilder::framing`] and
+    /// [`InstrumentBuilder::block_terminator`] do not apply. [`InstrumentBuilder::echo`],
+    /// [`InstrumentBuilder::reconnect`] and [`InstrumentBuilder::flush_before_query`]
+    /// are not supported either and ignored.
+    pub fn connect_usbtmc<D: BulkTransfer>(self, device: D) -> UsbtmcInstrument<D> {
+        UsbtmcInstrument::with_config(device, self)
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::transport::Connection;
+use crate::{Error, Result, ScpiDeserialize, ScpiRequest, ScpiSerialize};
+
+/// Connection shared between threads.
+///
+/// Each operation locks the connection for a complete request/response exchange,
+/// so traffic of different callers is never interleaved. Consequently, a long
+/// operation blocks all other callers, consider a tight default timeout and
+/// [`SharedInstrument::query_with_timeout`] for slow operations.
+#[derive(Debug)]
+pub struct SharedInstrument<C> {
+    connection: Arc<Mutex<C>>,
+}
+
+impl<C> Clone for SharedInstrument<C> {
+    fn clone(&self) -> Self {
+        Self {
+            connection: self.connection.clone(),
+        }
+    }
+}
+
+impl<C: Connection> SharedInstrument<C> {
+    pub fn new(connection: C) -> Self {
+        Self {
+            connection: Arc::new(Mutex::new(connection)),
+        }
+    }
+
+    /// Lock the connection for an exchange spanning multiple operations.
+    pub fn lock(&self) -> Result<MutexGuard<'_, C>> {
+        self.connection.lock().map_err(|_| {
+            Error::Transport("Connection poisoned by a panic during an exchange".to_string())
+        })
+    }
+
+    pub fn execute<T: ScpiSerialize + ?Sized>(&self, command: &T) -> Result<()> {
+        self.lock()?.execute(command)
+    }
+
+    pub fn query<R: ScpiRequest + ?Sized>(&self, request: &R) -> Result<R::Response>
+    where
+        R::Response: ScpiDeserialize,
+    {
+        self.lock()?.query(request)
+    }
+
+    pub fn query_with_timeout<R: ScpiRequest + ?Sized>(
+        &self,
+        request: &R,
+        timeout: Duration,
+    ) -> Result<R::Response>
+    where
+        R::Response: ScpiDeserialize,
+    {
+        self.lock()?.query_with_timeout(request, timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::transport::TcpInstrument;
+    use crate::{impl_scpi_request, impl_scpi_serialize};
+
+    struct Echo {
+        value: u32,
+    }
+    impl_scpi_serialize!(Echo, ["ECHO? ", value]);
+    impl_scpi_request!(Echo, u32);
+
+    #[test]
+    fn concurrent_queries() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.set_nodelay(true).unwrap();
+            let reader = BufReader::new(stream.try_clone().unwrap());
+            for line in reader.lines() {
+                let line = line.unwrap();
+                let value = line.strip_prefix("ECHO? ").unwrap();
+                // answer in two segments to provoke interleaving
+                stream.write_all(value.as_bytes()).unwrap();
+                thread::sleep(Duration::from_millis(1));
+                stream.write_all(b"\n").unwrap();
+            }
+        });
+
+        let instrument = SharedInstrument::new(TcpInstrument::connect(address).unwrap());
+        let workers: Vec<_> = (0..4)
+            .map(|worker| {
+                let instrument = instrument.clone();
+                thread::spawn(move || {
+                    for index in 0..10 {
+                        let value = worker * 100 + index;
+                        assert_eq!(instrument.query(&Echo { value }).unwrap(), value);
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        drop(instrument);
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,1008 @@
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+
+use crate::batch::{CommandBatch, CommandScript, QueryBatch};
+use crate::block::parse_block_header;
+use crate::containers::Separated;
+use crate::transport::{Connection, Framing, InstrumentBuilder, Terminator, Traffic};
+use crate::{Error, Result, ScpiDeserialize, ScpiRequest};
+
+/// Instrument connected via a raw TCP socket.
+#[derive(Debug)]
+pub struct TcpInstrument {
+    stream: TcpStream,
+    address: SocketAddr,
+    config: InstrumentBuilder,
+    buffer: Vec<u8>,
+    scan: MessageScan,
+}
+
+impl TcpInstrument {
+    /// Connect with the default configuration, see [`InstrumentBuilder`].
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Self> {
+        InstrumentBuilder::new().connect(address)
+    }
+
+    pub(crate) fn connect_with(
+        address: impl ToSocketAddrs,
+        config: InstrumentBuilder,
+    ) -> Result<Self> {
+        let mut last_error = None;
+        for address in address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, config.timeout) {
+                Ok(stream) => return Self::from_stream(stream, config),
+                Err(error) => last_error = Some(timeout_error(error, config.timeout)),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            Error::Transport("Address resolved to no socket address".to_string())
+        }))
+    }
+
+    fn from_stream(stream: TcpStream, config: InstrumentBuilder) -> Result<Self> {
+        configure(&stream, config.timeout)?;
+        Ok(Self {
+            address: stream.peer_addr()?,
+            stream,
+            config,
+            buffer: Vec::new(),
+            scan: MessageScan::default(),
+        })
+    }
+
+    fn reconnect(&mut self) -> Result<()> {
+        let stream = TcpStream::connect_timeout(&self.address, self.config.timeout)
+            .map_err(|e| timeout_error(e, self.config.timeout))?;
+        configure(&stream, self.config.timeout)?;
+        self.stream = stream;
+        self.buffer.clear();
+        self.scan = MessageScan::default();
+        if let Some(hook) = &self.config.on_traffic {
+            hook.call(Traffic::Reconnected);
+        }
+        Ok(())
+    }
+
+    /// Send a query and receive its response, reconnecting and retrying once
+    /// if enabled and the connection was lost.
+    fn exchange(&mut self, query: &str) -> Result<String> {
+        let exchange = |instrument: &mut Self| {
+            if instrument.config.flush_before_query {
+                instrument.flush_input()?;
+            }
+            instrument.send(query)?;
+            instrument.receive()
+        };
+        match exchange(self) {
+            Err(error) if self.config.reconnect && is_connection_lost(&error) => {
+                self.reconnect()?;
+                exchange(self)
+            }
+            result => result,
+        }
+    }
+
+    /// Detect the terminator of responses and use it from now on, returning it.
+    ///
+    /// Sends `*IDN?` and reads its response up to the first `\n`, a preceding `\r`
+    /// indicates [`Terminator::CrLf`], otherwise [`Terminator::Lf`]. If no `\n` arrives
+    /// within the timeout, a response ending with `\r` indicates [`Terminator::Cr`].
+    /// Any other response is ambiguous and the configured terminator is kept.
+    /// The `*IDN?` itself is sent with the configured terminator.
+    pub fn autodetect_terminator(&mut self) -> Result<Terminator> {
+        self.send("*IDN?")?;
+        let mut chunk = vec![0; self.config.read_buffer_size];
+        let newline = loop {
+            if let Some(index) = self.buffer.iter().position(|byte| *byte == b'\n') {
+                break Some(index);
+            }
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Ok(count) => {
+                    if let Some(hook) = &self.config.on_traffic {
+                        hook.call(Traffic::Received(&chunk[..count]));
+                    }
+                    self.buffer.extend_from_slice(&chunk[..count]);
+                }
+                Err(error) => match timeout_error(error, self.config.timeout) {
+                    Error::Timeout { .. } if !self.buffer.is_empty() => break None,
+                    error => return Err(error),
+                },
+            }
+        };
+
+        let detected = match newline {
+            Some(index) if index > 0 && self.buffer[index - 1] == b'\r' => Some(Terminator::CrLf),
+            Some(_) => Some(Terminator::Lf),
+            None if self.buffer.ends_with(b"\r") => Some(Terminator::Cr),
+            None => None,
+        };
+        let end = newline.map_or(self.buffer.len(), |index| index + 1);
+        self.buffer.drain(..end);
+        self.scan = MessageScan::default();
+        if let Some(terminator) = detected {
+            self.config.terminator = terminator;
+        }
+        Ok(self.config.terminator)
+    }
+
+    fn take_message(&mut self) -> Option<Vec<u8>> {
+        let terminator = self.config.terminator.as_str().as_bytes();
+        let (delimiter, terminated_blocks) = match &self.config.framing {
+            Framing::Terminator => (terminator, self.config.block_terminator),
+            Framing::Prompt(prompt) => (prompt.as_bytes(), true),
+        };
+        let (index, consumed) =
+            self.scan
+                .find_message_end(&self.buffer, delimiter, terminated_blocks)?;
+        let mut message: Vec<u8> = self.buffer.drain(..index + consumed).collect();
+        self.scan = MessageScan::default();
+        message.truncate(index);
+        if matches!(self.config.framing, Framing::Prompt(_)) && message.ends_with(terminator) {
+            message.truncate(message.len() - terminator.len());
+        }
+        Some(message)
+    }
+
+    fn read_message(&mut self) -> Result<Vec<u8>> {
+        let mut chunk = vec![0; self.config.read_buffer_size];
+        loop {
+            if let Some(message) = self.take_message() {
+                return Ok(message);
+            }
+            let limit = self.config.max_response_bytes;
+            if self.buffer.len() > limit {
+                self.buffer.clear();
+                self.scan = MessageScan::default();
+                return Err(Error::ResponseTooLarge { limit });
+            }
+            let count = self
+                .stream
+                .read(&mut chunk)
+                .map_err(|e| timeout_error(e, self.config.timeout))?;
+            if count == 0 {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+            if let Some(hook) = &self.config.on_traffic {
+                hook.call(Traffic::Received(&chunk[..count]));
+            }
+            self.buffer.extend_from_slice(&chunk[..count]);
+        }
+    }
+
+    /// Discard the echo of a sent message, which must match exactly.
+    /// Each line of a multi-line message, e.g. a script, is echoed on its own.
+    fn discard_echo(&mut self, message: &str) -> Result<()> {
+        let terminator = self.config.terminator.as_str();
+        for line in message.split(terminator) {
+            let echo = self.read_message()?;
+            if echo != line.as_bytes() {
+                return Err(Error::Transport(format!(
+                    "Expected echo of `{line}`, received `{}`",
+                    String::from_utf8_lossy(&echo)
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn configure(stream: &TcpStream, timeout: Duration) -> Result<()> {
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    stream.set_nodelay(true)?;
+    Ok(())
+}
+
+fn is_connection_lost(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::Io(error) if matches!(
+            error.kind(),
+            io::ErrorKind::BrokenPipe
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::UnexpectedEof
+        )
+    )
+}
+
+/// Distinguish expired timeouts from other I/O errors,
+/// depending on the platform these are reported as `WouldBlock` or `TimedOut`.
+pub(super) fn timeout_error(error: io::Error, after: Duration) -> Error {
+    match error.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Error::Timeout { after },
+        _ => error.into(),
+    }
+}
+
+/// Progress of the search for the end of the first buffered message, kept across reads
+/// so each received byte is scanned only once, also for responses spanning many reads.
+#[derive(Debug, Default)]
+struct MessageScan {
+    /// Offset of the first byte not scanned yet.
+    offset: usize,
+    /// Whether `offset` is inside a quoted string.
+    quoted: bool,
+    /// End of a definite length block ending the message, whose payload is incomplete.
+    block_end: Option<usize>,
+}
+
+impl MessageScan {
+    /// Find the delimiter ending the first message, skipping over the payload of
+    /// definite length blocks and quoted strings. Without `terminated_blocks`, the message
+    /// ends directly after the first block. Returns the length of the message and of
+    /// the delimiter, `None` if the message is not yet complete.
+    ///
+    /// The scan resumes where the previous call stopped, so it must be reset
+    /// whenever bytes are removed from the front of the buffer.
+    fn find_message_end(
+        &mut self,
+        buffer: &[u8],
+        delimiter: &[u8],
+        terminated_blocks: bool,
+    ) -> Option<(usize, usize)> {
+        if let Some(end) = self.block_end {
+            return (end <= buffer.len()).then_some((end, 0));
+        }
+        while self.offset < buffer.len() {
+            let index = self.offset;
+            let rest = &buffer[index..];
+            match rest[0] {
+                b'"' => self.quoted = !self.quoted,
+                _ if self.quoted => {}
+                _ if rest.starts_with(delimiter) => return Some((index, delimiter.len())),
+                // the delimiter may be split across reads, resume at its start
+                _ if delimiter.starts_with(rest) => return None,
+                b'#' => match parse_block_header(rest) {
+                    Ok(Some((header, length))) => {
+                        self.offset += header + length;
+                        if !terminated_blocks {
+                            self.block_end = Some(self.offset);
+                            return (self.offset <= buffer.len()).then_some((self.offset, 0));
+                        }
+                        continue;
+                    }
+                    Ok(None) => return None,
+                    Err(_) => {}
+                },
+                _ => {}
+            }
+            self.offset += 1;
+        }
+        None
+    }
+}
+
+impl Connection for TcpInstrument {
+    fn send(&mut self, message: &str) -> Result<()> {
+        let mut data = Vec::with_capacity(message.len() + 2);
+        data.extend_from_slice(message.as_bytes());
+        data.extend_from_slice(self.config.terminator.as_str().as_bytes());
+        if let Some(hook) = &self.config.on_traffic {
+            hook.call(Traffic::Sent(&data));
+        }
+        if self.config.dry_run {
+            return Ok(());
+        }
+        self.stream
+            .write_all(&data)
+            .map_err(|e| timeout_error(e, self.config.timeout))?;
+        if self.config.echo {
+            self.discard_echo(message)?;
+        }
+        thread::sleep(self.config.inter_command_delay);
+        Ok(())
+    }
+
+    fn receive_bytes(&mut self) -> Result<Vec<u8>> {
+        if self.config.dry_run {
+            return Ok(Vec::new());
+        }
+        self.read_message()
+    }
+
+    fn query_str(&mut self, query: &str) -> Result<String> {
+        self.exchange(query)
+    }
+
+    fn query_batch<T, R>(&mut self, queries: T) -> Result<R>
+    where
+        QueryBatch<T>: ScpiRequest<Response = Separated<R, ';'>>,
+        Separated<R, ';'>: ScpiDeserialize,
+    {
+        let message = self.config.batch_message(&QueryBatch(queries));
+        let response = self.exchange(&message)?;
+        self.config
+            .decode_response::<Separated<R, ';'>>(&response)
+            .map(|responses| responses.0)
+    }
+
+    fn execute_batch(&mut self, batch: &CommandBatch<'_>) -> Result<()> {
+        let message = self.config.batch_message(batch);
+        self.send(&message)
+    }
+
+    fn execute_script(&mut self, script: &CommandScript<'_>) -> Result<()> {
+        let message = self.config.script_message(script);
+        self.send(&message)
+    }
+
+    fn query<R: ScpiRequest + ?Sized>(&mut self, request: &R) -> Result<R::Response>
+    where
+        R::Response: ScpiDeserialize,
+    {
+        let response = self.exchange(&request.serialize_to_string())?;
+        self.config.decode_response(&response)
+    }
+
+    fn flush_input(&mut self) -> Result<()> {
+        let mut discarded = std::mem::take(&mut self.buffer);
+        self.scan = MessageScan::default();
+        self.stream.set_nonblocking(true)?;
+        let mut chunk = vec![0; self.config.read_buffer_size];
+        let result = loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => break Ok(()),
+                Ok(count) => discarded.extend_from_slice(&chunk[..count]),
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break Ok(()),
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => break Err(error),
+            }
+        };
+        self.stream.set_nonblocking(false)?;
+        if let Some(hook) = &self.config.on_traffic
+            && !discarded.is_empty()
+        {
+            hook.call(Traffic::Discarded(&discarded));
+        }
+        Ok(result?)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.config.timeout
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<()> {
+        self.stream.set_read_timeout(Some(timeout))?;
+        self.stream.set_write_timeout(Some(timeout))?;
+        self.config.timeout = timeout;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    use super::*;
+    use crate::block::Endianness;
+    use crate::status::StatusByte;
+    use crate::trace::DataFormat;
+    use crate::transport::Terminator;
+    use crate::{impl_scpi_request, impl_scpi_serialize};
+
+    struct VoltageQuery;
+    impl_scpi_serialize!(VoltageQuery, ["VOLT?"]);
+    impl_scpi_request!(VoltageQuery, f32);
+
+    /// Serve a single connection, answering each received line with the given chunks.
+    fn serve(responses: Vec<Vec<&'static [u8]>>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut received = Vec::new();
+            for chunks in responses {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                received.push(line);
+                for chunk in chunks {
+                    stream.write_all(chunk).unwrap();
+                    stream.flush().unwrap();
+                    thread::sleep(Duration::from_millis(10));
+                }
+            }
+            // keep the connection open until the client disconnects
+            reader.read_line(&mut String::new()).unwrap();
+            received
+        });
+        (address, handle)
+    }
+
+    #[test]
+    fn query() {
+        let (address, server) = serve(vec![vec![b"1.5\n"], vec![b"2.", b"5\n"]]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 1.5);
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 2.5);
+        drop(instrument);
+        assert_eq!(server.join().unwrap(), vec!["VOLT?\n", "VOLT?\n"]);
+    }
+
+    #[test]
+    fn raw_strings() {
+        let (address, server) = serve(vec![vec![], vec![b"ACME,1234,0,1.0\n"]]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        instrument.write_str("*RST").unwrap();
+        assert_eq!(instrument.query_str("*IDN?").unwrap(), "ACME,1234,0,1.0");
+        drop(instrument);
+        assert_eq!(server.join().unwrap(), vec!["*RST\n", "*IDN?\n"]);
+    }
+
+    #[test]
+    fn serial_poll() {
+        let (address, server) = serve(vec![vec![b"+80\n"]]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        let status = instrument.serial_poll().unwrap();
+        assert!(status.contains(StatusByte::MESSAGE_AVAILABLE | StatusByte::REQUEST_SERVICE));
+        drop(instrument);
+        assert_eq!(server.join().unwrap(), vec!["*STB?\n"]);
+    }
+
+    struct Initiate;
+    impl_scpi_serialize!(Initiate, ["INIT"]);
+
+    #[test]
+    fn run_to_completion() {
+        let (address, server) = serve(vec![
+            vec![],
+            vec![b"+0\n"],
+            vec![],
+            vec![],
+            vec![b"+0\n"],
+            vec![b"+32\n"],
+            vec![b"+1\n"],
+        ]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        instrument
+            .run_to_completion(&Initiate, Duration::from_secs(1))
+            .unwrap();
+        drop(instrument);
+        assert_eq!(
+            server.join().unwrap(),
+            vec![
+                "*ESE 1\n", "*ESR?\n", "INIT\n", "*OPC\n", "*STB?\n", "*STB?\n", "*ESR?\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn run_to_completion_timeout() {
+        let (address, server) = serve(vec![vec![], vec![b"+0\n"], vec![], vec![], vec![b"+0\n"]]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        let error = instrument
+            .run_to_completion(&Initiate, Duration::ZERO)
+            .unwrap_err();
+        assert!(matches!(error, Error::Timeout { .. }));
+        drop(instrument);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn echo() {
+        let (address, server) = serve(vec![
+            vec![b"*RST\n"],
+            vec![b"VOLT?\n", b"1.5\n"],
+            vec![b"VOLT\n2.5\n"],
+        ]);
+        let mut instrument = InstrumentBuilder::new()
+            .echo(true)
+            .connect(address)
+            .unwrap();
+        instrument.write_str("*RST").unwrap();
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 1.5);
+        let error = instrument.query(&VoltageQuery).unwrap_err();
+        assert!(matches!(error, Error::Transport(_)), "{error}");
+        drop(instrument);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn receive_quoted() {
+        let (address, server) = serve(vec![vec![b"-100,\"Command\nerror; \"\"#3\"\"\"", b"\n"]]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        assert_eq!(
+            instrument.query_str("SYST:ERR?").unwrap(),
+            "-100,\"Command\nerror; \"\"#3\"\"\""
+        );
+        drop(instrument);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn autodetect_terminator() {
+        for (response, expected) in [
+            (&b"ACME,1\r\n"[..], Terminator::CrLf),
+            (b"ACME,1\n", Terminator::Lf),
+            (b"ACME,1\r", Terminator::Cr),
+            (b"ACME,1", Terminator::Lf),
+        ] {
+            let (address, server) = serve(vec![vec![response]]);
+            let mut instrument = InstrumentBuilder::new()
+                .timeout(Duration::from_millis(100))
+                .connect(address)
+                .unwrap();
+            assert_eq!(instrument.autodetect_terminator().unwrap(), expected);
+            assert!(instrument.buffer.is_empty());
+            drop(instrument);
+            assert_eq!(server.join().unwrap(), vec!["*IDN?\n"]);
+        }
+    }
+
+    #[test]
+    fn autodetect_terminator_query() {
+        let (address, server) = serve(vec![vec![b"ACME\r", b"\n"], vec![b"1.5\r\n"]]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        assert_eq!(
+            instrument.autodetect_terminator().unwrap(),
+            Terminator::CrLf
+        );
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 1.5);
+        drop(instrument);
+        // the query after detection is sent with the detected terminator
+        let received = server.join().unwrap();
+        assert_eq!(received, vec!["*IDN?\n", "VOLT?\r\n"]);
+    }
+
+    #[test]
+    fn assert_no_errors() {
+        let (address, server) = serve(vec![
+            vec![b"+0,\"No error\"\n"],
+            vec![b"-113,\"Undefined header\"\n"],
+            vec![b"-222,\"Data out of range\"\n"],
+            vec![b"+0,\"No error\"\n"],
+        ]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        instrument.assert_no_errors().unwrap();
+        let error = instrument.assert_no_errors().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Instrument reported errors: -113,\"Undefined header\"; -222,\"Data out of range\""
+        );
+        assert!(matches!(error, Error::Instrument(errors) if errors.len() == 2));
+        drop(instrument);
+        assert_eq!(server.join().unwrap(), vec!["SYST:ERR?\n"; 4]);
+    }
+
+    #[test]
+    fn execute_checked() {
+        let (address, server) = serve(vec![
+            vec![],
+            vec![b"+0,\"No error\"\n"],
+            vec![],
+            vec![b"-108,\"Parameter not allowed\"\n"],
+            vec![b"+0,\"No error\"\n"],
+        ]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        instrument.execute_checked("VOLT 1").unwrap();
+        let error = instrument.execute_checked("VOLT 1,2").unwrap_err();
+        assert!(matches!(error, Error::Instrument(errors) if errors[0].code == -108));
+        drop(instrument);
+        assert_eq!(
+            server.join().unwrap(),
+            vec![
+                "VOLT 1\n",
+                "SYST:ERR?\n",
+                "VOLT 1,2\n",
+                "SYST:ERR?\n",
+                "SYST:ERR?\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn dry_run() {
+        let (address, server) = serve(vec![]);
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let log = sent.clone();
+        let mut instrument = InstrumentBuilder::new()
+            .dry_run(true)
+            .on_traffic(move |t| {
+                if let Traffic::Sent(data) = t {
+                    log.lock().unwrap().push(data.to_vec());
+                }
+            })
+            .connect(address)
+            .unwrap();
+        instrument.write_str("*RST").unwrap();
+        assert_eq!(instrument.query_str("*IDN?").unwrap(), "");
+        assert!(instrument.query(&VoltageQuery).is_err());
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![b"*RST\n".to_vec(), b"*IDN?\n".to_vec(), b"VOLT?\n".to_vec()]
+        );
+        drop(instrument);
+        assert!(server.join().unwrap().is_empty());
+    }
+
+    #[test]
+    fn inter_command_delay() {
+        let (address, server) = serve(vec![vec![], vec![b"1.5\n"]]);
+        let delay = Duration::from_millis(50);
+        let mut instrument = InstrumentBuilder::new()
+            .inter_command_delay(delay)
+            .connect(address)
+            .unwrap();
+        let start = Instant::now();
+        instrument.write_str("VOLT 1.5").unwrap();
+        assert!(start.elapsed() >= delay);
+        assert_eq!(instrument.query_str("VOLT?").unwrap(), "1.5");
+        assert!(start.elapsed() >= 2 * delay);
+        drop(instrument);
+        assert_eq!(server.join().unwrap(), vec!["VOLT 1.5\n", "VOLT?\n"]);
+    }
+
+    #[test]
+    fn execute_script() {
+        let (address, server) = serve(vec![vec![], vec![], vec![]]);
+        let writes = Arc::new(Mutex::new(0));
+        let count = writes.clone();
+        let mut instrument = InstrumentBuilder::new()
+            .terminator(Terminator::CrLf)
+            .on_traffic(move |t| {
+                if let Traffic::Sent(_) = t {
+                    *count.lock().unwrap() += 1;
+                }
+            })
+            .connect(address)
+            .unwrap();
+        let script = CommandScript::new()
+            .command("*RST")
+            .command("VOLT 1.5")
+            .command("OUTP ON");
+        instrument.execute_script(&script).unwrap();
+        assert_eq!(*writes.lock().unwrap(), 1);
+        drop(instrument);
+        assert_eq!(
+            server.join().unwrap(),
+            vec!["*RST\r\n", "VOLT 1.5\r\n", "OUTP ON\r\n"]
+        );
+    }
+
+    #[test]
+    fn clear_status_before_batch() {
+        let (address, server) = serve(vec![vec![], vec![], vec![], vec![], vec![b"1.5;2\n"]]);
+        let mut instrument = InstrumentBuilder::new()
+            .clear_status_before_batch(true)
+            .connect(address)
+            .unwrap();
+        let batch = CommandBatch::new().command("VOLT 1.5").command("CURR 0.1");
+        instrument.execute_batch(&batch).unwrap();
+        instrument
+            .execute_script(&CommandScript::new().command("VOLT 2"))
+            .unwrap();
+        instrument.write_str("OUTP ON").unwrap();
+        let (first, second) = instrument
+            .query_batch((VoltageQuery, VoltageQuery))
+            .unwrap();
+        assert_eq!((first, second), (1.5, 2.0));
+        drop(instrument);
+        assert_eq!(
+            server.join().unwrap(),
+            vec![
+                "*CLS;VOLT 1.5;CURR 0.1\n",
+                "*CLS\n",
+                "VOLT 2\n",
+                "OUTP ON\n",
+                "*CLS;VOLT?;VOLT?\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn flush_input() {
+        let (address, server) = serve(vec![vec![b"1.5\n", b"stale\n"], vec![b"2.5\n"]]);
+        let discarded = Arc::new(Mutex::new(Vec::new()));
+        let log = discarded.clone();
+        let mut instrument = InstrumentBuilder::new()
+            .flush_before_query(true)
+            .on_traffic(move |t| {
+                if let Traffic::Discarded(data) = t {
+                    log.lock().unwrap().extend_from_slice(data);
+                }
+            })
+            .connect(address)
+            .unwrap();
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 1.5);
+        // let the stale line arrive
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 2.5);
+        assert_eq!(*discarded.lock().unwrap(), b"stale\n");
+        instrument.flush_input().unwrap();
+        drop(instrument);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn receive_block() {
+        let (address, server) = serve(vec![vec![b"#18\n\n", b"\x00\xff", b"#\n\n\n", b"\n"]]);
+        let mut instrument = InstrumentBuilder::new()
+            .read_buffer_size(3)
+            .connect(address)
+            .unwrap();
+        instrument.send("CURV?").unwrap();
+        assert_eq!(
+            instrument.receive_bytes().unwrap(),
+            b"#18\n\n\x00\xff#\n\n\n"
+        );
+        drop(instrument);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn receive_unterminated_block() {
+        let (address, server) = serve(vec![vec![b"#15ab\n", b"de1.5\n"], vec![b"#10"]]);
+        let mut instrument = InstrumentBuilder::new()
+            .block_terminator(false)
+            .connect(address)
+            .unwrap();
+        instrument.send("CURV?").unwrap();
+        assert_eq!(instrument.receive_bytes().unwrap(), b"#15ab\nde");
+        assert_eq!(instrument.receive().unwrap(), "1.5");
+        instrument.send("CURV?").unwrap();
+        assert_eq!(instrument.receive_bytes().unwrap(), b"#10");
+        drop(instrument);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn receive_large_response() {
+        let mut response = "1.25,".repeat(1_000_000).into_bytes();
+        *response.last_mut().unwrap() = b'\n';
+        let (address, server) = serve(vec![vec![response.leak()]]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        let start = Instant::now();
+        let trace = instrument.query_str("CURV?").unwrap();
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(trace.len(), 5_000_000 - 1);
+        assert!(trace.starts_with("1.25,1.25,") && trace.ends_with(",1.25"));
+        drop(instrument);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn message_scan_resumes() {
+        let mut scan = MessageScan::default();
+        let mut buffer = b"\"a\r\nb".to_vec();
+        assert_eq!(scan.find_message_end(&buffer, b"\r\n", true), None);
+        buffer.extend_from_slice(b"\"\r");
+        assert_eq!(scan.find_message_end(&buffer, b"\r\n", true), None);
+        assert_eq!(scan.offset, 6);
+        buffer.extend_from_slice(b"\n");
+        assert_eq!(scan.find_message_end(&buffer, b"\r\n", true), Some((6, 2)));
+
+        let mut scan = MessageScan::default();
+        let mut buffer = b"1,#".to_vec();
+        assert_eq!(scan.find_message_end(&buffer, b"\n", false), None);
+        buffer.extend_from_slice(b"13\n");
+        assert_eq!(scan.find_message_end(&buffer, b"\n", false), None);
+        assert_eq!(scan.block_end, Some(8));
+        buffer.extend_from_slice(b"\n\nx");
+        assert_eq!(scan.find_message_end(&buffer, b"\n", false), Some((8, 0)));
+    }
+
+    #[test]
+    fn response_too_large() {
+        let (address, server) = serve(vec![vec![&b"0123456789"[..]; 4]]);
+        let mut instrument = InstrumentBuilder::new()
+            .max_response_bytes(32)
+            .connect(address)
+            .unwrap();
+        let error = instrument.query_str("CURV?").unwrap_err();
+        assert!(
+            matches!(error, Error::ResponseTooLarge { limit: 32 }),
+            "{error}"
+        );
+        drop(instrument);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn query_trace() {
+        let (address, server) = serve(vec![
+            vec![b"1.5,-2\n"],
+            vec![b"#18\x00\x00\xc0?\x00\x00\x00\xc0\n"],
+        ]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        let ascii = instrument.query_trace("CURV?", DataFormat::Ascii).unwrap();
+        let binary = instrument
+            .query_trace("CURV?", DataFormat::Real32(Endianness::Little))
+            .unwrap();
+        assert_eq!(ascii, vec![1.5, -2.0]);
+        assert_eq!(binary, ascii);
+        drop(instrument);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn builder() {
+        let (address, server) = serve(vec![vec![b"1.5\r", b"\n"]]);
+        let mut instrument = InstrumentBuilder::new()
+            .terminator(Terminator::CrLf)
+            .read_buffer_size(2)
+            .connect(address)
+            .unwrap();
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 1.5);
+        drop(instrument);
+        assert_eq!(server.join().unwrap(), vec!["VOLT?\r\n"]);
+    }
+
+    #[test]
+    fn prompt_framing() {
+        let (address, server) = serve(vec![vec![b"1.5\r\nSC", b"PI> "], vec![b"2.5SCPI> "]]);
+        let mut instrument = InstrumentBuilder::new()
+            .terminator(Terminator::CrLf)
+            .framing(Framing::Prompt("SCPI> ".to_string()))
+            .connect(address)
+            .unwrap();
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 1.5);
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 2.5);
+        drop(instrument);
+        assert_eq!(server.join().unwrap(), vec!["VOLT?\r\n", "VOLT?\r\n"]);
+    }
+
+    #[test]
+    fn traffic_hook() {
+        let (address, server) = serve(vec![vec![b"1.", b"5\n"]]);
+        let traffic = Arc::new(Mutex::new(Vec::new()));
+        let log = traffic.clone();
+        let mut instrument = InstrumentBuilder::new()
+            .on_traffic(move |t| {
+                let entry = match t {
+                    Traffic::Sent(data) => ('>', data.to_vec()),
+                    Traffic::Received(data) => ('<', data.to_vec()),
+                    Traffic::Reconnected => ('!', Vec::new()),
+                    Traffic::Discarded(data) => ('x', data.to_vec()),
+                };
+                log.lock().unwrap().push(entry);
+            })
+            .connect(address)
+            .unwrap();
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 1.5);
+        drop(instrument);
+        server.join().unwrap();
+
+        let traffic = traffic.lock().unwrap();
+        assert_eq!(traffic[0], ('>', b"VOLT?\n".to_vec()));
+        let received: Vec<u8> = traffic[1..]
+            .iter()
+            .inspect(|(direction, _)| assert_eq!(*direction, '<'))
+            .flat_map(|(_, data)| data.clone())
+            .collect();
+        assert_eq!(received, b"1.5\n");
+    }
+
+    #[test]
+    fn traffic_hook_at_eof() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(stream.try_clone().unwrap())
+                .read_line(&mut line)
+                .unwrap();
+            stream.write_all(b"1.").unwrap();
+        });
+        let traffic = Arc::new(Mutex::new(Vec::new()));
+        let log = traffic.clone();
+        let mut instrument = InstrumentBuilder::new()
+            .on_traffic(move |t| {
+                if let Traffic::Received(data) = t {
+                    log.lock().unwrap().push(data.to_vec());
+                }
+            })
+            .connect(address)
+            .unwrap();
+        assert!(matches!(
+            instrument.query_str("VOLT?"),
+            Err(Error::Io(error)) if error.kind() == io::ErrorKind::UnexpectedEof
+        ));
+        server.join().unwrap();
+        assert_eq!(*traffic.lock().unwrap(), vec![b"1.".to_vec()]);
+    }
+
+    #[test]
+    fn reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let mut received = Vec::new();
+            for response in [&b"1.5\n"[..], b"2.5\n"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut line = String::new();
+                BufReader::new(stream.try_clone().unwrap())
+                    .read_line(&mut line)
+                    .unwrap();
+                received.push(line);
+                stream.write_all(response).unwrap();
+            }
+            received
+        });
+
+        let events = Arc::new(Mutex::new(0));
+        let reconnects = events.clone();
+        let mut instrument = InstrumentBuilder::new()
+            .reconnect(true)
+            .on_traffic(move |t| {
+                if t == Traffic::Reconnected {
+                    *reconnects.lock().unwrap() += 1;
+                }
+            })
+            .connect(address)
+            .unwrap();
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 1.5);
+        // the server closed the first connection
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 2.5);
+        assert_eq!(*events.lock().unwrap(), 1);
+        drop(instrument);
+        assert_eq!(server.join().unwrap(), vec!["VOLT?\n", "VOLT?\n"]);
+    }
+
+    #[test]
+    fn no_reconnect() {
+        let (address, server) = serve(vec![]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        instrument
+            .stream
+            .shutdown(std::net::Shutdown::Read)
+            .unwrap();
+        let error = instrument.query(&VoltageQuery).unwrap_err();
+        assert!(is_connection_lost(&error), "{error}");
+        drop(instrument);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn self_test() {
+        let (address, server) = serve(vec![vec![b"+0\n"], vec![b"+12\n"]]);
+        let mut instrument = TcpInstrument::connect(address).unwrap();
+        assert!(instrument.self_test().unwrap().passed());
+        assert_eq!(instrument.self_test().unwrap().code(), 12);
+        assert_eq!(instrument.timeout(), Duration::from_secs(5));
+        drop(instrument);
+        assert_eq!(server.join().unwrap(), vec!["*TST?\n", "*TST?\n"]);
+    }
+
+    #[test]
+    fn query_with_timeout() {
+        let (address, server) = serve(vec![vec![b"1.5\n"], vec![]]);
+        let mut instrument = InstrumentBuilder::new()
+            .timeout(Duration::from_millis(50))
+            .connect(address)
+            .unwrap();
+        let response = instrument.query_with_timeout(&VoltageQuery, Duration::from_secs(2));
+        assert_eq!(response.unwrap(), 1.5);
+        assert_eq!(instrument.timeout(), Duration::from_millis(50));
+
+        let response = instrument.query_with_timeout(&VoltageQuery, Duration::from_millis(20));
+        assert!(
+            matches!(response, Err(Error::Timeout { after }) if after == Duration::from_millis(20))
+        );
+        assert_eq!(instrument.timeout(), Duration::from_millis(50));
+        drop(instrument);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn timeout() {
+        let (address, server) = serve(vec![vec![]]);
+        let mut instrument = InstrumentBuilder::new()
+            .timeout(Duration::from_millis(50))
+            .connect(address)
+            .unwrap();
+        assert!(matches!(
+            instrument.query(&VoltageQuery),
+            Err(Error::Timeout { after }) if after == Duration::from_millis(50)
+        ));
+        drop(instrument);
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,372 @@
+use std::thread;
+use std::time::Duration;
+
+use crate::batch::{CommandBatch, CommandScript, QueryBatch};
+use crate::containers::Separated;
+use crate::transport::{Connection, InstrumentBuilder, Traffic};
+use crate::{Error, Result, ScpiDeserialize, ScpiRequest};
+
+const DEV_DEP_MSG_OUT: u8 = 1;
+const REQUEST_DEV_DEP_MSG_IN: u8 = 2;
+const DEV_DEP_MSG_IN: u8 = 2;
+
+/// Size of the bulk header preceding each transfer.
+const HEADER_SIZE: usize = 12;
+/// `bmTransferAttributes` bit marking the last transfer of a message.
+const ATTRIBUTE_EOM: u8 = 0x01;
+
+/// Bulk endpoints of a USBTMC interface, e.g. a claimed `rusb` device handle.
+///
+/// Implementers transfer raw data, the USBTMC framing is handled by [`UsbtmcInstrument`].
+/// Failures should be reported as [`Error::Timeout`] or [`Error::Transport`].
+pub trait BulkTransfer {
+    /// Write one transfer to the bulk-OUT endpoint.
+    fn write_bulk(&mut self, data: &[u8], timeout: Duration) -> Result<()>;
+
+    /// Read one transfer from the bulk-IN endpoint into `buffer`, returning its length.
+    fn read_bulk(&mut self, buffer: &mut [u8], timeout: Duration) -> Result<usize>;
+}
+
+/// Bulk header of a USBTMC transfer (USBTMC 1.0, section 3.1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BulkHeader {
+    message_id: u8,
+    tag: u8,
+    transfer_size: u32,
+    attributes: u8,
+}
+
+impl BulkHeader {
+    fn encode(&self) -> [u8; HEADER_SIZE] {
+        let mut header = [0; HEADER_SIZE];
+        header[0] = self.message_id;
+        header[1] = self.tag;
+        header[2] = !self.tag;
+        header[4..8].copy_from_slice(&self.transfer_size.to_le_bytes());
+        header[8] = self.attributes;
+        header
+    }
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        let Some(header) = data.first_chunk::<HEADER_SIZE>() else {
+            return Err(Error::Transport("Truncated USBTMC bulk header".to_string()));
+        };
+        if header[2] != !header[1] {
+            return Err(Error::Transport(format!(
+                "Corrupt USBTMC bulk header, bTag {} with inverse {}",
+                header[1], header[2]
+            )));
+        }
+        Ok(Self {
+            message_id: header[0],
+            tag: header[1],
+            transfer_size: u32::from_le_bytes(header[4..8].try_into().unwrap()),
+            attributes: header[8],
+        })
+    }
+}
+
+/// Instrument connected via USBTMC, the USB Test & Measurement Class,
+/// on top of user provided bulk endpoints, see [`BulkTransfer`].
+///
+/// Received messages are framed by the EOM bit of the bulk header. The terminator
+/// is still appended to sent messages and stripped from received ones, if present.
+/// See [`InstrumentBuilder::connect_usbtmc`] for the options that do not apply.
+/// Use [`Resource`](crate::transport::Resource) to parse a VISA resource string like
+/// `USB0::0x0957::0x1796::MY12345678::INSTR` into the IDs of the device to open.
+///
+/// Only the bulk endpoints are used, so [`Connection::serial_poll`] queries `*STB?`
+/// instead of the USB488 `READ_STATUS_BYTE` control request.
+#[derive(Debug)]
+pub struct UsbtmcInstrument<D> {
+    device: D,
+    tag: u8,
+    config: InstrumentBuilder,
+}
+
+impl<D: BulkTransfer> UsbtmcInstrument<D> {
+    /// Use the bulk endpoints with the default configuration, see [`InstrumentBuilder`].
+    pub fn new(device: D) -> Self {
+        InstrumentBuilder::new().connect_usbtmc(device)
+    }
+
+    pub(crate) fn with_config(device: D, config: InstrumentBuilder) -> Self {
+        Self {
+            device,
+            tag: 0,
+            config,
+        }
+    }
+
+    /// Get back the bulk endpoints, e.g. to release the interface.
+    pub fn into_inner(self) -> D {
+        self.device
+    }
+
+    /// Next `bTag`, counting from 1 to 255 and skipping 0 on wraparound.
+    fn next_tag(&mut self) -> u8 {
+        self.tag = self.tag.checked_add(1).unwrap_or(1);
+        self.tag
+    }
+}
+
+impl<D: BulkTransfer> Connection for UsbtmcInstrument<D> {
+    fn send(&mut self, message: &str) -> Result<()> {
+        let mut data = Vec::with_capacity(message.len() + 2);
+        data.extend_from_slice(message.as_bytes());
+        data.extend_from_slice(self.config.terminator.as_str().as_bytes());
+        if let Some(hook) = &self.config.on_traffic {
+            hook.call(Traffic::Sent(&data));
+        }
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let header = BulkHeader {
+            message_id: DEV_DEP_MSG_OUT,
+            tag: self.next_tag(),
+            transfer_size: data.len() as u32,
+            attributes: ATTRIBUTE_EOM,
+        };
+        let mut transfer = header.encode().to_vec();
+        transfer.extend_from_slice(&data);
+        transfer.resize(transfer.len().next_multiple_of(4), 0);
+        self.device.write_bulk(&transfer, self.config.timeout)?;
+        thread::sleep(self.config.inter_command_delay);
+        Ok(())
+    }
+
+    fn query<R: ScpiRequest + ?Sized>(&mut self, request: &R) -> Result<R::Response>
+    where
+        R::Response: ScpiDeserialize,
+    {
+        self.execute(request)?;
+        let response = self.receive()?;
+        self.config.decode_response(&response)
+    }
+
+    fn query_batch<T, R>(&mut self, queries: T) -> Result<R>
+    where
+        QueryBatch<T>: ScpiRequest<Response = Separated<R, ';'>>,
+        Separated<R, ';'>: ScpiDeserialize,
+    {
+        let message = self.config.batch_message(&QueryBatch(queries));
+        self.send(&message)?;
+        let response = self.receive()?;
+        self.config
+            .decode_response::<Separated<R, ';'>>(&response)
+            .map(|responses| responses.0)
+    }
+
+    fn execute_batch(&mut self, batch: &CommandBatch<'_>) -> Result<()> {
+        let message = self.config.batch_message(batch);
+        self.send(&message)
+    }
+
+    fn execute_script(&mut self, script: &CommandScript<'_>) -> Result<()> {
+        let message = self.config.script_message(script);
+        self.send(&message)
+    }
+
+    fn receive_bytes(&mut self) -> Result<Vec<u8>> {
+        if self.config.dry_run {
+            return Ok(Vec::new());
+        }
+        let size = self.config.read_buffer_size;
+        let mut buffer = vec![0; HEADER_SIZE + size.next_multiple_of(4)];
+        let mut message = Vec::new();
+        loop {
+            let tag = self.next_tag();
+            let request = BulkHeader {
+                message_id: REQUEST_DEV_DEP_MSG_IN,
+                tag,
+                transfer_size: size as u32,
+                attributes: 0,
+            };
+            self.device
+                .write_bulk(&request.encode(), self.config.timeout)?;
+            let count = self.device.read_bulk(&mut buffer, self.config.timeout)?;
+
+            let header = BulkHeader::decode(&buffer[..count])?;
+            if header.message_id != DEV_DEP_MSG_IN || header.tag != tag {
+                return Err(Error::Transport(format!(
+                    "Expected DEV_DEP_MSG_IN with bTag {tag}, received MsgID {} with bTag {}",
+                    header.message_id, header.tag
+                )));
+            }
+            let Some(data) = buffer[HEADER_SIZE..count].get(..header.transfer_size as usize) else {
+                return Err(Error::Transport(format!(
+                    "USBTMC transfer declares {} bytes, but contains {}",
+                    header.transfer_size,
+                    count - HEADER_SIZE
+                )));
+            };
+            if let Some(hook) = &self.config.on_traffic {
+                hook.call(Traffic::Received(data));
+            }
+            message.extend_from_slice(data);
+            let limit = self.config.max_response_bytes;
+            if message.len() > limit + self.config.terminator.as_str().len() {
+                return Err(Error::ResponseTooLarge { limit });
+            }
+            if header.attributes & ATTRIBUTE_EOM != 0 {
+                break;
+            }
+        }
+        let terminator = self.config.terminator.as_str().as_bytes();
+        if message.ends_with(terminator) {
+            message.truncate(message.len() - terminator.len());
+        }
+        Ok(message)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.config.timeout
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<()> {
+        self.config.timeout = timeout;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+    use crate::{impl_scpi_request, impl_scpi_serialize};
+
+    struct VoltageQuery;
+    impl_scpi_serialize!(VoltageQuery, ["VOLT?"]);
+    impl_scpi_request!(VoltageQuery, f32);
+
+    /// Device answering each message with its response, split into transfers.
+    #[derive(Default)]
+    struct MockDevice {
+        responses: VecDeque<Vec<&'static [u8]>>,
+        pending: VecDeque<&'static [u8]>,
+        written: Vec<Vec<u8>>,
+        requested: Option<BulkHeader>,
+    }
+
+    impl BulkTransfer for MockDevice {
+        fn write_bulk(&mut self, data: &[u8], _timeout: Duration) -> Result<()> {
+            assert_eq!(data.len() % 4, 0);
+            let header = BulkHeader::decode(data).unwrap();
+            match header.message_id {
+                DEV_DEP_MSG_OUT => {
+                    assert_eq!(header.attributes, ATTRIBUTE_EOM);
+                    let size = header.transfer_size as usize;
+                    self.written
+                        .push(data[HEADER_SIZE..HEADER_SIZE + size].to_vec());
+                    self.pending = self.responses.pop_front().unwrap_or_default().into();
+                }
+                REQUEST_DEV_DEP_MSG_IN => self.requested = Some(header),
+                id => panic!("unexpected MsgID {id}"),
+            }
+            Ok(())
+        }
+
+        fn read_bulk(&mut self, buffer: &mut [u8], timeout: Duration) -> Result<usize> {
+            let request = self.requested.take().expect("read without request");
+            let Some(data) = self.pending.pop_front() else {
+                return Err(Error::Timeout { after: timeout });
+            };
+            assert!(data.len() <= request.transfer_size as usize);
+            let header = BulkHeader {
+                message_id: DEV_DEP_MSG_IN,
+                tag: request.tag,
+                transfer_size: data.len() as u32,
+                attributes: if self.pending.is_empty() {
+                    ATTRIBUTE_EOM
+                } else {
+                    0
+                },
+            };
+            let transfer = [&header.encode()[..], data].concat();
+            let count = transfer.len().next_multiple_of(4);
+            buffer[..transfer.len()].copy_from_slice(&transfer);
+            buffer[transfer.len()..count].fill(0);
+            Ok(count)
+        }
+    }
+
+    #[test]
+    fn bulk_header() {
+        let header = BulkHeader {
+            message_id: DEV_DEP_MSG_OUT,
+            tag: 1,
+            transfer_size: 0x0102,
+            attributes: ATTRIBUTE_EOM,
+        };
+        let encoded = header.encode();
+        assert_eq!(encoded, [1, 1, 0xFE, 0, 0x02, 0x01, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(BulkHeader::decode(&encoded).unwrap(), header);
+
+        assert!(BulkHeader::decode(&encoded[..11]).is_err());
+        let mut corrupt = encoded;
+        corrupt[2] = 0;
+        assert!(
+            BulkHeader::decode(&corrupt)
+                .unwrap_err()
+                .to_string()
+                .contains("Corrupt")
+        );
+    }
+
+    #[test]
+    fn tags_skip_zero() {
+        let mut instrument = UsbtmcInstrument::new(MockDevice::default());
+        assert_eq!(instrument.next_tag(), 1);
+        instrument.tag = 255;
+        assert_eq!(instrument.next_tag(), 1);
+    }
+
+    #[test]
+    fn query() {
+        let device = MockDevice {
+            responses: VecDeque::from([vec![&b"1.5\n"[..]], vec![&b"ACME,"[..], b"1234\n"]]),
+            ..Default::default()
+        };
+        let mut instrument = InstrumentBuilder::new()
+            .read_buffer_size(6)
+            .connect_usbtmc(device);
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 1.5);
+        assert_eq!(instrument.query_str("*IDN?").unwrap(), "ACME,1234");
+        let error = instrument.receive_bytes().unwrap_err();
+        assert!(matches!(error, Error::Timeout { .. }), "{error}");
+        assert_eq!(
+            instrument.into_inner().written,
+            vec![b"VOLT?\n".to_vec(), b"*IDN?\n".to_vec()]
+        );
+    }
+
+    struct WrongTag;
+
+    impl BulkTransfer for WrongTag {
+        fn write_bulk(&mut self, _data: &[u8], _timeout: Duration) -> Result<()> {
+            Ok(())
+        }
+
+        fn read_bulk(&mut self, buffer: &mut [u8], _timeout: Duration) -> Result<usize> {
+            let header = BulkHeader {
+                message_id: DEV_DEP_MSG_IN,
+                tag: 7,
+                transfer_size: 0,
+                attributes: ATTRIBUTE_EOM,
+            };
+            buffer[..HEADER_SIZE].copy_from_slice(&header.encode());
+            Ok(HEADER_SIZE)
+        }
+    }
+
+    #[test]
+    fn wrong_tag() {
+        let error = UsbtmcInstrument::new(WrongTag).receive().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Transport error: Expected DEV_DEP_MSG_IN with bTag 1, received MsgID 2 with bTag 7"
+        );
+    }
+}
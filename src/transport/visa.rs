@@ -0,0 +1,412 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::batch::{CommandBatch, CommandScript, QueryBatch};
+use crate::containers::Separated;
+use crate::status::StatusByte;
+use crate::transport::{Connection, InstrumentBuilder, TcpInstrument, Vxi11Instrument};
+use crate::{Error, Result, ScpiDeserialize, ScpiRequest};
+
+/// Parsed VISA resource string, e.g. `TCPIP0::192.168.1.5::inst0::INSTR`.
+///
+/// Interface and resource class are case insensitive, the board number defaults to 0
+/// and a missing resource class to `INSTR`. USB vendor and product IDs are accepted
+/// in decimal and hexadecimal with a `0x` prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Resource {
+    /// `TCPIP[board]::host[::device][::INSTR]`, a VXI-11 device, `inst0` by default.
+    TcpipInstr {
+        board: u16,
+        host: String,
+        device: String,
+    },
+    /// `TCPIP[board]::host::port::SOCKET`, a raw SCPI socket.
+    TcpipSocket { board: u16, host: String, port: u16 },
+    /// `USB[board]::vendor::product::serial[::interface][::INSTR]`, a USBTMC device.
+    UsbInstr {
+        board: u16,
+        vendor_id: u16,
+        product_id: u16,
+        serial: String,
+        interface: Option<u8>,
+    },
+    /// `GPIB[board]::primary[::secondary][::INSTR]`.
+    GpibInstr {
+        board: u16,
+        primary: u8,
+        secondary: Option<u8>,
+    },
+}
+
+impl FromStr for Resource {
+    type Err = Error;
+
+    fn from_str(resource: &str) -> Result<Self> {
+        let invalid = |reason| invalid_resource(resource, reason);
+
+        let mut fields: Vec<&str> = resource.split("::").collect();
+        let socket = match fields.last() {
+            Some(class) if fields.len() > 1 && class.eq_ignore_ascii_case("SOCKET") => true,
+            Some(class) if fields.len() > 1 && class.eq_ignore_ascii_case("INSTR") => false,
+            _ => {
+                fields.push("INSTR");
+                false
+            }
+        };
+        fields.pop();
+        if fields.iter().any(|field| field.is_empty()) {
+            return Err(invalid("empty field"));
+        }
+
+        let interface = fields[0].trim_end_matches(|c: char| c.is_ascii_digit());
+        let board = match &fields[0][interface.len()..] {
+            "" => 0,
+            digits => digits
+                .parse()
+                .map_err(|_| invalid("board number out of range"))?,
+        };
+        let id = |field: &str| {
+            match field
+                .strip_prefix("0x")
+                .or_else(|| field.strip_prefix("0X"))
+            {
+                Some(hex) => u16::from_str_radix(hex, 16),
+                None => field.parse(),
+            }
+            .map_err(|_| invalid("invalid USB ID"))
+        };
+
+        match (
+            interface.to_ascii_uppercase().as_str(),
+            &fields[1..],
+            socket,
+        ) {
+            ("TCPIP", [host], false) => Ok(Resource::TcpipInstr {
+                board,
+                host: host.to_string(),
+                device: "inst0".to_string(),
+            }),
+            ("TCPIP", [host, device], false) => Ok(Resource::TcpipInstr {
+                board,
+                host: host.to_string(),
+                device: device.to_string(),
+            }),
+            ("TCPIP", [host, port], true) => Ok(Resource::TcpipSocket {
+                board,
+                host: host.to_string(),
+                port: number(resource, port)?,
+            }),
+            ("USB", [vendor, product, serial, interface @ ..], false) if interface.len() <= 1 => {
+                Ok(Resource::UsbInstr {
+                    board,
+                    vendor_id: id(vendor)?,
+                    product_id: id(product)?,
+                    serial: serial.to_string(),
+                    interface: interface
+                        .first()
+                        .map(|field| number(resource, field))
+                        .transpose()?,
+                })
+            }
+            ("GPIB", [primary, secondary @ ..], false) if secondary.len() <= 1 => {
+                Ok(Resource::GpibInstr {
+                    board,
+                    primary: number(resource, primary)?,
+                    secondary: secondary
+                        .first()
+                        .map(|field| number(resource, field))
+                        .transpose()?,
+                })
+            }
+            ("TCPIP" | "USB" | "GPIB", _, _) => Err(invalid("unexpected number of fields")),
+            _ => Err(invalid("unknown interface")),
+        }
+    }
+}
+
+fn invalid_resource(resource: &str, reason: &str) -> Error {
+    Error::Visa(format!("Invalid resource `{resource}`, {reason}"))
+}
+
+fn number<T: FromStr>(resource: &str, field: &str) -> Result<T> {
+    field
+        .parse()
+        .map_err(|_| invalid_resource(resource, "invalid number"))
+}
+
+/// Canonical form of the resource string, with board number and resource class.
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Resource::TcpipInstr {
+                board,
+                host,
+                device,
+            } => write!(f, "TCPIP{board}::{host}::{device}::INSTR"),
+            Resource::TcpipSocket { board, host, port } => {
+                write!(f, "TCPIP{board}::{host}::{port}::SOCKET")
+            }
+            Resource::UsbInstr {
+                board,
+                vendor_id,
+                product_id,
+                serial,
+                interface,
+            } => {
+                write!(
+                    f,
+                    "USB{board}::0x{vendor_id:04X}::0x{product_id:04X}::{serial}"
+                )?;
+                if let Some(interface) = interface {
+                    write!(f, "::{interface}")?;
+                }
+                f.write_str("::INSTR")
+            }
+            Resource::GpibInstr {
+                board,
+                primary,
+                secondary,
+            } => {
+                write!(f, "GPIB{board}::{primary}")?;
+                if let Some(secondary) = secondary {
+                    write!(f, "::{secondary}")?;
+                }
+                f.write_str("::INSTR")
+            }
+        }
+    }
+}
+
+/// Instrument addressed by a VISA resource string, see [`Resource`].
+///
+/// This does not bind a VISA library, the resource is opened with the transports of
+/// this crate instead: `TCPIP::INSTR` resources via VXI-11 and `TCPIP::SOCKET` resources
+/// via a raw socket. Other interfaces, including HiSLIP devices, fail with [`Error::Visa`].
+/// USB devices can be used with [`UsbtmcInstrument`](crate::transport::UsbtmcInstrument).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum VisaInstrument {
+    Tcp(TcpInstrument),
+    Vxi11(Vxi11Instrument),
+}
+
+macro_rules! dispatch {
+    ($self:expr, $connection:ident => $body:expr) => {
+        match $self {
+            VisaInstrument::Tcp($connection) => $body,
+            VisaInstrument::Vxi11($connection) => $body,
+        }
+    };
+}
+
+impl VisaInstrument {
+    /// Connect with the default configuration, see [`InstrumentBuilder`].
+    pub fn connect(resource: &str) -> Result<Self> {
+        InstrumentBuilder::new().connect_visa(resource)
+    }
+
+    pub(crate) fn connect_with(resource: &str, config: InstrumentBuilder) -> Result<Self> {
+        match resource.parse()? {
+            Resource::TcpipInstr { device, .. }
+                if device.to_ascii_lowercase().starts_with("hislip") =>
+            {
+                Err(Error::Visa(format!(
+                    "HiSLIP resource `{resource}` is not supported"
+                )))
+            }
+            Resource::TcpipInstr { host, device, .. } => {
+                Vxi11Instrument::connect_with(&host, &device, config).map(Self::Vxi11)
+            }
+            Resource::TcpipSocket { host, port, .. } => {
+                TcpInstrument::connect_with((host.as_str(), port), config).map(Self::Tcp)
+            }
+            Resource::UsbInstr { .. } => Err(Error::Visa(format!(
+                "USB resource `{resource}` requires opening the device, see `UsbtmcInstrument`"
+            ))),
+            _ => Err(Error::Visa(format!(
+                "Interface of resource `{resource}` is not supported, only TCPIP is"
+            ))),
+        }
+    }
+}
+
+impl Connection for VisaInstrument {
+    fn send(&mut self, message: &str) -> Result<()> {
+        dispatch!(self, connection => connection.send(message))
+    }
+
+    fn receive_bytes(&mut self) -> Result<Vec<u8>> {
+        dispatch!(self, connection => connection.receive_bytes())
+    }
+
+    fn receive(&mut self) -> Result<String> {
+        dispatch!(self, connection => connection.receive())
+    }
+
+    fn flush_input(&mut self) -> Result<()> {
+        dispatch!(self, connection => connection.flush_input())
+    }
+
+    fn timeout(&self) -> Duration {
+        dispatch!(self, connection => connection.timeout())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<()> {
+        dispatch!(self, connection => connection.set_timeout(timeout))
+    }
+
+    fn query_str(&mut self, query: &str) -> Result<String> {
+        dispatch!(self, connection => connection.query_str(query))
+    }
+
+    fn execute_batch(&mut self, batch: &CommandBatch<'_>) -> Result<()> {
+        dispatch!(self, connection => connection.execute_batch(batch))
+    }
+
+    fn execute_script(&mut self, script: &CommandScript<'_>) -> Result<()> {
+        dispatch!(self, connection => connection.execute_script(script))
+    }
+
+    fn query<R: ScpiRequest + ?Sized>(&mut self, request: &R) -> Result<R::Response>
+    where
+        R::Response: ScpiDeserialize,
+    {
+        dispatch!(self, connection => connection.query(request))
+    }
+
+    fn query_batch<T, R>(&mut self, queries: T) -> Result<R>
+    where
+        QueryBatch<T>: ScpiRequest<Response = Separated<R, ';'>>,
+        Separated<R, ';'>: ScpiDeserialize,
+    {
+        dispatch!(self, connection => connection.query_batch(queries))
+    }
+
+    fn serial_poll(&mut self) -> Result<StatusByte> {
+        dispatch!(self, connection => connection.serial_poll())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    fn parse(resource: &str) -> Resource {
+        resource.parse().unwrap()
+    }
+
+    fn parse_error(resource: &str) -> String {
+        match resource.parse::<Resource>() {
+            Err(Error::Visa(message)) => message,
+            other => panic!("expected a VISA error for `{resource}`, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn tcpip() {
+        let instr = Resource::TcpipInstr {
+            board: 0,
+            host: "192.168.1.5".to_string(),
+            device: "inst0".to_string(),
+        };
+        assert_eq!(parse("TCPIP0::192.168.1.5::inst0::INSTR"), instr);
+        assert_eq!(parse("TCPIP::192.168.1.5::INSTR"), instr);
+        assert_eq!(parse("tcpip::192.168.1.5"), instr);
+        assert_eq!(instr.to_string(), "TCPIP0::192.168.1.5::inst0::INSTR");
+
+        let socket = parse("TCPIP1::scope.lab::5025::SOCKET");
+        assert_eq!(
+            socket,
+            Resource::TcpipSocket {
+                board: 1,
+                host: "scope.lab".to_string(),
+                port: 5025,
+            }
+        );
+        assert_eq!(socket.to_string(), "TCPIP1::scope.lab::5025::SOCKET");
+    }
+
+    #[test]
+    fn usb_and_gpib() {
+        let usb = parse("USB0::0x0957::0x1796::MY12345678::INSTR");
+        assert_eq!(
+            usb,
+            Resource::UsbInstr {
+                board: 0,
+                vendor_id: 0x0957,
+                product_id: 0x1796,
+                serial: "MY12345678".to_string(),
+                interface: None,
+            }
+        );
+        assert_eq!(usb.to_string(), "USB0::0x0957::0x1796::MY12345678::INSTR");
+        assert_eq!(
+            parse("USB::2391::6038::MY12345678::1"),
+            Resource::UsbInstr {
+                board: 0,
+                vendor_id: 0x0957,
+                product_id: 0x1796,
+                serial: "MY12345678".to_string(),
+                interface: Some(1),
+            }
+        );
+
+        let gpib = parse("GPIB0::22::3::INSTR");
+        assert_eq!(
+            gpib,
+            Resource::GpibInstr {
+                board: 0,
+                primary: 22,
+                secondary: Some(3),
+            }
+        );
+        assert_eq!(gpib.to_string(), "GPIB0::22::3::INSTR");
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(parse_error("ASRL1::INSTR").contains("unknown interface"));
+        assert!(parse_error("TCPIP0::").contains("empty field"));
+        assert!(parse_error("TCPIP0::host::inst0::5025::SOCKET").contains("number of fields"));
+        assert!(parse_error("TCPIP0::host::http::SOCKET").contains("invalid number"));
+        assert!(parse_error("USB0::0xZZ::0x1796::SN::INSTR").contains("invalid USB ID"));
+        assert!(parse_error("GPIB0::256::INSTR").contains("invalid number"));
+        assert!(parse_error("TCPIP70000::host::INSTR").contains("board number"));
+    }
+
+    #[test]
+    fn unsupported() {
+        let error = VisaInstrument::connect("USB0::0x0957::0x1796::SN::INSTR").unwrap_err();
+        assert!(matches!(error, Error::Visa(message) if message.contains("UsbtmcInstrument")));
+        let error = VisaInstrument::connect("GPIB0::22::INSTR").unwrap_err();
+        assert!(matches!(error, Error::Visa(message) if message.contains("only TCPIP")));
+        let error = VisaInstrument::connect("TCPIP0::127.0.0.1::hislip0::INSTR").unwrap_err();
+        assert!(matches!(error, Error::Visa(message) if message.contains("HiSLIP")));
+    }
+
+    #[test]
+    fn socket() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            stream.write_all(b"ACME,1234,0,1.0\n").unwrap();
+            line
+        });
+
+        let resource = format!("TCPIP0::127.0.0.1::{port}::SOCKET");
+        let mut instrument = VisaInstrument::connect(&resource).unwrap();
+        assert!(matches!(instrument, VisaInstrument::Tcp(_)));
+        assert_eq!(instrument.query_str("*IDN?").unwrap(), "ACME,1234,0,1.0");
+        assert_eq!(server.join().unwrap(), "*IDN?\n");
+    }
+}
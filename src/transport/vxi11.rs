@@ -0,0 +1,599 @@
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+
+use crate::batch::{CommandBatch, CommandScript, QueryBatch};
+use crate::containers::Separated;
+use crate::status::StatusByte;
+use crate::transport::tcp::timeout_error;
+use crate::transport::{Connection, InstrumentBuilder, Traffic};
+use crate::{Error, Result, ScpiDeserialize, ScpiRequest};
+
+const PORTMAPPER_PORT: u16 = 111;
+const PORTMAPPER_PROGRAM: u32 = 100000;
+const PORTMAPPER_VERSION: u32 = 2;
+const PORTMAPPER_GETPORT: u32 = 3;
+const IPPROTO_TCP: u32 = 6;
+
+const DEVICE_CORE_PROGRAM: u32 = 0x0607AF;
+const DEVICE_CORE_VERSION: u32 = 1;
+const CREATE_LINK: u32 = 10;
+const DEVICE_WRITE: u32 = 11;
+const DEVICE_READ: u32 = 12;
+const DEVICE_READSTB: u32 = 13;
+const DESTROY_LINK: u32 = 23;
+
+/// `Device_Flags` marking the last chunk of a message.
+const FLAG_END: u32 = 0x08;
+/// `reason` of `device_read` indicating the end of a message.
+const REASON_END: u32 = 0x04;
+/// `Device_ErrorCode` of an expired `io_timeout`.
+const ERROR_IO_TIMEOUT: u32 = 15;
+
+/// Additional socket timeout, so the instrument reports an expired `io_timeout` first.
+const TIMEOUT_MARGIN: Duration = Duration::from_secs(1);
+
+/// Encoder of XDR (RFC 4506) data.
+#[derive(Default)]
+struct XdrWriter(Vec<u8>);
+
+impl XdrWriter {
+    fn u32(mut self, value: u32) -> Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    fn opaque(mut self, data: &[u8]) -> Self {
+        self = self.u32(data.len() as u32);
+        self.0.extend_from_slice(data);
+        self.0.resize(self.0.len().next_multiple_of(4), 0);
+        self
+    }
+}
+
+/// Decoder of XDR (RFC 4506) data.
+struct XdrReader<'a>(&'a [u8]);
+
+impl<'a> XdrReader<'a> {
+    fn u32(&mut self) -> Result<u32> {
+        let Some((value, rest)) = self.0.split_first_chunk() else {
+            return Err(Error::Transport("Truncated RPC reply".to_string()));
+        };
+        self.0 = rest;
+        Ok(u32::from_be_bytes(*value))
+    }
+
+    fn opaque(&mut self) -> Result<&'a [u8]> {
+        let len = self.u32()? as usize;
+        let padded = len.next_multiple_of(4);
+        if self.0.len() < padded {
+            return Err(Error::Transport("Truncated RPC reply".to_string()));
+        }
+        let (data, rest) = self.0.split_at(padded);
+        self.0 = rest;
+        Ok(&data[..len])
+    }
+}
+
+/// ONC RPC (RFC 5531) client over TCP with record marking.
+#[derive(Debug)]
+struct RpcClient {
+    stream: TcpStream,
+    xid: u32,
+}
+
+impl RpcClient {
+    fn connect(address: &SocketAddr, timeout: Duration) -> Result<Self> {
+        let stream =
+            TcpStream::connect_timeout(address, timeout).map_err(|e| timeout_error(e, timeout))?;
+        stream.set_nodelay(true)?;
+        let client = Self { stream, xid: 0 };
+        client.set_timeout(timeout)?;
+        Ok(client)
+    }
+
+    fn set_timeout(&self, timeout: Duration) -> Result<()> {
+        self.stream.set_read_timeout(Some(timeout))?;
+        self.stream.set_write_timeout(Some(timeout))?;
+        Ok(())
+    }
+
+    /// Call a procedure and return the encoded results.
+    fn call(&mut self, program: u32, version: u32, procedure: u32, args: &[u8]) -> Result<Vec<u8>> {
+        self.xid = self.xid.wrapping_add(1);
+        let call = XdrWriter::default()
+            .u32(self.xid)
+            .u32(0) // CALL
+            .u32(2) // RPC version
+            .u32(program)
+            .u32(version)
+            .u32(procedure)
+            .u32(0) // AUTH_NONE credentials
+            .opaque(&[])
+            .u32(0) // AUTH_NONE verifier
+            .opaque(&[]);
+        let mut record = XdrWriter::default().u32(0x8000_0000 | (call.0.len() + args.len()) as u32);
+        record.0.extend_from_slice(&call.0);
+        record.0.extend_from_slice(args);
+        let timeout = self.stream.write_timeout()?.unwrap_or_default();
+        self.stream
+            .write_all(&record.0)
+            .map_err(|e| timeout_error(e, timeout))?;
+
+        loop {
+            let reply = self.read_record()?;
+            let mut reader = XdrReader(&reply);
+            if reader.u32()? != self.xid {
+                continue; // stale reply of an earlier, timed out call
+            }
+            if reader.u32()? != 1 {
+                return Err(Error::Transport("Expected RPC reply".to_string()));
+            }
+            if reader.u32()? != 0 {
+                return Err(Error::Transport("RPC call denied".to_string()));
+            }
+            reader.u32()?; // verifier flavor
+            reader.opaque()?;
+            match reader.u32()? {
+                0 => return Ok(reader.0.to_vec()),
+                status => {
+                    return Err(Error::Transport(format!(
+                        "RPC call not accepted, status {status}"
+                    )));
+                }
+            }
+        }
+    }
+
+    fn read_record(&mut self) -> Result<Vec<u8>> {
+        let timeout = self.stream.read_timeout()?.unwrap_or_default();
+        let mut record = Vec::new();
+        loop {
+            let mut header = [0; 4];
+            self.stream
+                .read_exact(&mut header)
+                .map_err(|e| timeout_error(e, timeout))?;
+            let header = u32::from_be_bytes(header);
+            let start = record.len();
+            record.resize(start + (header & 0x7FFF_FFFF) as usize, 0);
+            self.stream
+                .read_exact(&mut record[start..])
+                .map_err(|e| timeout_error(e, timeout))?;
+            if header & 0x8000_0000 != 0 {
+                return Ok(record);
+            }
+        }
+    }
+}
+
+fn device_error(code: u32, timeout: Duration) -> Result<()> {
+    let description = match code {
+        0 => return Ok(()),
+        ERROR_IO_TIMEOUT => return Err(Error::Timeout { after: timeout }),
+        1 => "syntax error",
+        3 => "device not accessible",
+        4 => "invalid link identifier",
+        5 => "parameter error",
+        6 => "channel not established",
+        8 => "operation not supported",
+        9 => "out of resources",
+        11 => "device locked by another link",
+        12 => "no lock held by this link",
+        17 => "I/O error",
+        21 => "invalid address",
+        23 => "abort",
+        29 => "channel already established",
+        _ => "unknown error",
+    };
+    Err(Error::Transport(format!(
+        "VXI-11 error {code}: {description}"
+    )))
+}
+
+/// Instrument connected via VXI-11, as commonly supported by LXI instruments.
+///
+/// Messages are framed by the END flag of the protocol, the terminator is still
+/// appended to sent messages and stripped from received ones, if present.
+/// See [`InstrumentBuilder::connect_vxi11`] for the options that do not apply.
+#[derive(Debug)]
+pub struct Vxi11Instrument {
+    client: RpcClient,
+    link: u32,
+    max_receive_size: usize,
+    config: InstrumentBuilder,
+}
+
+impl Vxi11Instrument {
+    /// Connect to the device, e.g. `inst0`, of `host`, with the default configuration.
+    pub fn connect(host: &str, device: &str) -> Result<Self> {
+        InstrumentBuilder::new().connect_vxi11(host, device)
+    }
+
+    pub(crate) fn connect_with(
+        host: &str,
+        device: &str,
+        config: InstrumentBuilder,
+    ) -> Result<Self> {
+        let address = (host, PORTMAPPER_PORT)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| Error::Transport("Address resolved to no socket address".to_string()))?;
+        Self::connect_via(address, device, config)
+    }
+
+    /// Look up the core channel with the portmapper at `portmapper` and create a link.
+    fn connect_via(
+        portmapper: SocketAddr,
+        device: &str,
+        config: InstrumentBuilder,
+    ) -> Result<Self> {
+        let args = XdrWriter::default()
+            .u32(DEVICE_CORE_PROGRAM)
+            .u32(DEVICE_CORE_VERSION)
+            .u32(IPPROTO_TCP)
+            .u32(0);
+        let reply = RpcClient::connect(&portmapper, config.timeout)?.call(
+            PORTMAPPER_PROGRAM,
+            PORTMAPPER_VERSION,
+            PORTMAPPER_GETPORT,
+            &args.0,
+        )?;
+        let port = XdrReader(&reply).u32()?;
+        let port = u16::try_from(port)
+            .ok()
+            .filter(|port| *port != 0)
+            .ok_or_else(|| Error::Transport("VXI-11 core channel not registered".to_string()))?;
+
+        let mut client =
+            RpcClient::connect(&SocketAddr::new(portmapper.ip(), port), config.timeout)?;
+        client.set_timeout(config.timeout + TIMEOUT_MARGIN)?;
+        let args = XdrWriter::default()
+            .u32(std::process::id())
+            .u32(0) // no lock
+            .u32(0) // lock timeout
+            .opaque(device.as_bytes());
+        let reply = client.call(
+            DEVICE_CORE_PROGRAM,
+            DEVICE_CORE_VERSION,
+            CREATE_LINK,
+            &args.0,
+        )?;
+        let mut reader = XdrReader(&reply);
+        device_error(reader.u32()?, config.timeout)?;
+        let link = reader.u32()?;
+        reader.u32()?; // abort port
+        let max_receive_size = (reader.u32()? as usize).max(1);
+        Ok(Self {
+            client,
+            link,
+            max_receive_size,
+            config,
+        })
+    }
+
+    fn io_timeout(&self) -> u32 {
+        self.config
+            .timeout
+            .as_millis()
+            .try_into()
+            .unwrap_or(u32::MAX)
+    }
+}
+
+impl Drop for Vxi11Instrument {
+    fn drop(&mut self) {
+        let args = XdrWriter::default().u32(self.link);
+        let _ = self.client.call(
+            DEVICE_CORE_PROGRAM,
+            DEVICE_CORE_VERSION,
+            DESTROY_LINK,
+            &args.0,
+        );
+    }
+}
+
+impl Connection for Vxi11Instrument {
+    fn send(&mut self, message: &str) -> Result<()> {
+        let mut data = Vec::with_capacity(message.len() + 2);
+        data.extend_from_slice(message.as_bytes());
+        data.extend_from_slice(self.config.terminator.as_str().as_bytes());
+        if let Some(hook) = &self.config.on_traffic {
+            hook.call(Traffic::Sent(&data));
+        }
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let mut chunks = data.chunks(self.max_receive_size).peekable();
+        while let Some(chunk) = chunks.next() {
+            let flags = if chunks.peek().is_none() { FLAG_END } else { 0 };
+            let args = XdrWriter::default()
+                .u32(self.link)
+                .u32(self.io_timeout())
+                .u32(0) // lock timeout
+                .u32(flags)
+                .opaque(chunk);
+            let reply = self.client.call(
+                DEVICE_CORE_PROGRAM,
+                DEVICE_CORE_VERSION,
+                DEVICE_WRITE,
+                &args.0,
+            )?;
+            let mut reader = XdrReader(&reply);
+            device_error(reader.u32()?, self.config.timeout)?;
+            if reader.u32()? as usize != chunk.len() {
+                return Err(Error::Transport(
+                    "Instrument accepted only part of the message".to_string(),
+                ));
+            }
+        }
+        thread::sleep(self.config.inter_command_delay);
+        Ok(())
+    }
+
+    fn query<R: ScpiRequest + ?Sized>(&mut self, request: &R) -> Result<R::Response>
+    where
+        R::Response: ScpiDeserialize,
+    {
+        self.execute(request)?;
+        let response = self.receive()?;
+        self.config.decode_response(&response)
+    }
+
+    fn query_batch<T, R>(&mut self, queries: T) -> Result<R>
+    where
+        QueryBatch<T>: ScpiRequest<Response = Separated<R, ';'>>,
+        Separated<R, ';'>: ScpiDeserialize,
+    {
+        let message = self.config.batch_message(&QueryBatch(queries));
+        self.send(&message)?;
+        let response = self.receive()?;
+        self.config
+            .decode_response::<Separated<R, ';'>>(&response)
+            .map(|responses| responses.0)
+    }
+
+    fn execute_batch(&mut self, batch: &CommandBatch<'_>) -> Result<()> {
+        let message = self.config.batch_message(batch);
+        self.send(&message)
+    }
+
+    fn execute_script(&mut self, script: &CommandScript<'_>) -> Result<()> {
+        let message = self.config.script_message(script);
+        self.send(&message)
+    }
+
+    fn receive_bytes(&mut self) -> Result<Vec<u8>> {
+        if self.config.dry_run {
+            return Ok(Vec::new());
+        }
+        let mut message = Vec::new();
+        loop {
+            let args = XdrWriter::default()
+                .u32(self.link)
+                .u32(self.config.read_buffer_size as u32)
+                .u32(self.io_timeout())
+                .u32(0) // lock timeout
+                .u32(0) // flags, no termination character
+                .u32(0); // termination character
+            let reply = self.client.call(
+                DEVICE_CORE_PROGRAM,
+                DEVICE_CORE_VERSION,
+                DEVICE_READ,
+                &args.0,
+            )?;
+            let mut reader = XdrReader(&reply);
+            device_error(reader.u32()?, self.config.timeout)?;
+            let reason = reader.u32()?;
+            let data = reader.opaque()?;
+            if let Some(hook) = &self.config.on_traffic {
+                hook.call(Traffic::Received(data));
+            }
+            message.extend_from_slice(data);
+            let limit = self.config.max_response_bytes;
+            if message.len() > limit + self.config.terminator.as_str().len() {
+                return Err(Error::ResponseTooLarge { limit });
+            }
+            if reason & REASON_END != 0 {
+                break;
+            }
+        }
+        let terminator = self.config.terminator.as_str().as_bytes();
+        if message.ends_with(terminator) {
+            message.truncate(message.len() - terminator.len());
+        }
+        Ok(message)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.config.timeout
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<()> {
+        self.client.set_timeout(timeout + TIMEOUT_MARGIN)?;
+        self.config.timeout = timeout;
+        Ok(())
+    }
+
+    /// Read the status byte with `device_readstb`, which works even while the instrument is busy.
+    fn serial_poll(&mut self) -> Result<StatusByte> {
+        let args = XdrWriter::default()
+            .u32(self.link)
+            .u32(0) // flags
+            .u32(0) // lock timeout
+            .u32(self.io_timeout());
+        let reply = self.client.call(
+            DEVICE_CORE_PROGRAM,
+            DEVICE_CORE_VERSION,
+            DEVICE_READSTB,
+            &args.0,
+        )?;
+        let mut reader = XdrReader(&reply);
+        device_error(reader.u32()?, self.config.timeout)?;
+        Ok(StatusByte(reader.u32()? as u8))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+    use crate::{impl_scpi_request, impl_scpi_serialize};
+
+    struct VoltageQuery;
+    impl_scpi_serialize!(VoltageQuery, ["VOLT?"]);
+    impl_scpi_request!(VoltageQuery, f32);
+
+    fn read_call(stream: &mut TcpStream) -> Option<(u32, u32, Vec<u8>)> {
+        let mut header = [0; 4];
+        stream.read_exact(&mut header).ok()?;
+        let mut record = vec![0; (u32::from_be_bytes(header) & 0x7FFF_FFFF) as usize];
+        stream.read_exact(&mut record).unwrap();
+        let mut reader = XdrReader(&record);
+        let xid = reader.u32().unwrap();
+        for _ in 0..4 {
+            reader.u32().unwrap(); // type, RPC version, program, version
+        }
+        let procedure = reader.u32().unwrap();
+        reader.u32().unwrap();
+        reader.opaque().unwrap();
+        reader.u32().unwrap();
+        reader.opaque().unwrap();
+        Some((xid, procedure, reader.0.to_vec()))
+    }
+
+    fn reply(stream: &mut TcpStream, xid: u32, results: XdrWriter) {
+        let reply = XdrWriter::default()
+            .u32(xid)
+            .u32(1) // REPLY
+            .u32(0) // accepted
+            .u32(0)
+            .opaque(&[])
+            .u32(0); // success
+        let mut record = XdrWriter::default()
+            .u32(0x8000_0000 | (reply.0.len() + results.0.len()) as u32)
+            .0;
+        record.extend_from_slice(&reply.0);
+        record.extend_from_slice(&results.0);
+        stream.write_all(&record).unwrap();
+    }
+
+    /// Serve the portmapper and a device with a maximum receive size of 4 bytes,
+    /// answering each message with `responses` in chunks of 2 bytes.
+    fn serve(mut responses: Vec<&'static [u8]>) -> (SocketAddr, thread::JoinHandle<Vec<Vec<u8>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let (xid, procedure, args) = read_call(&mut stream).unwrap();
+            assert_eq!(procedure, PORTMAPPER_GETPORT);
+            assert_eq!(&args[..4], DEVICE_CORE_PROGRAM.to_be_bytes());
+            reply(
+                &mut stream,
+                xid,
+                XdrWriter::default().u32(address.port() as u32),
+            );
+
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = Vec::new();
+            let mut message = Vec::new();
+            let mut pending: Vec<u8> = Vec::new();
+            while let Some((xid, procedure, args)) = read_call(&mut stream) {
+                let mut reader = XdrReader(&args);
+                let results = match procedure {
+                    CREATE_LINK => {
+                        for _ in 0..3 {
+                            reader.u32().unwrap();
+                        }
+                        assert_eq!(reader.opaque().unwrap(), b"inst0");
+                        XdrWriter::default().u32(0).u32(7).u32(0).u32(4)
+                    }
+                    DEVICE_WRITE => {
+                        assert_eq!(reader.u32().unwrap(), 7);
+                        reader.u32().unwrap();
+                        reader.u32().unwrap();
+                        let flags = reader.u32().unwrap();
+                        let data = reader.opaque().unwrap();
+                        assert!(data.len() <= 4);
+                        message.extend_from_slice(data);
+                        if flags & FLAG_END != 0 {
+                            received.push(std::mem::take(&mut message));
+                            if !responses.is_empty() {
+                                pending = responses.remove(0).to_vec();
+                            }
+                        }
+                        XdrWriter::default().u32(0).u32(data.len() as u32)
+                    }
+                    DEVICE_READ if pending.is_empty() => XdrWriter::default()
+                        .u32(ERROR_IO_TIMEOUT)
+                        .u32(0)
+                        .opaque(&[]),
+                    DEVICE_READ => {
+                        let chunk: Vec<u8> = pending.drain(..pending.len().min(2)).collect();
+                        let reason = if pending.is_empty() { REASON_END } else { 0 };
+                        XdrWriter::default().u32(0).u32(reason).opaque(&chunk)
+                    }
+                    DEVICE_READSTB => XdrWriter::default().u32(0).u32(0x50),
+                    DESTROY_LINK => XdrWriter::default().u32(0),
+                    _ => XdrWriter::default().u32(8),
+                };
+                reply(&mut stream, xid, results);
+            }
+            received
+        });
+        (address, handle)
+    }
+
+    #[test]
+    fn xdr() {
+        let data = XdrWriter::default().u32(1).opaque(b"abcde").u32(2).0;
+        assert_eq!(data.len(), 4 + 4 + 8 + 4);
+        let mut reader = XdrReader(&data);
+        assert_eq!(reader.u32().unwrap(), 1);
+        assert_eq!(reader.opaque().unwrap(), b"abcde");
+        assert_eq!(reader.u32().unwrap(), 2);
+        assert!(reader.u32().is_err());
+    }
+
+    #[test]
+    fn query() {
+        let (address, server) = serve(vec![b"1.5\n", b"2.25\n"]);
+        let mut instrument =
+            Vxi11Instrument::connect_via(address, "inst0", InstrumentBuilder::new()).unwrap();
+        assert_eq!(instrument.query(&VoltageQuery).unwrap(), 1.5);
+        assert_eq!(instrument.query_str("SOUR:VOLT?").unwrap(), "2.25");
+        let status = instrument.serial_poll().unwrap();
+        assert!(status.contains(StatusByte::MESSAGE_AVAILABLE | StatusByte::REQUEST_SERVICE));
+        drop(instrument);
+        assert_eq!(
+            server.join().unwrap(),
+            vec![b"VOLT?\n".to_vec(), b"SOUR:VOLT?\n".to_vec()]
+        );
+    }
+
+    #[test]
+    fn device_timeout() {
+        let (address, server) = serve(vec![]);
+        let mut instrument =
+            Vxi11Instrument::connect_via(address, "inst0", InstrumentBuilder::new()).unwrap();
+        let error = instrument.receive_bytes().unwrap_err();
+        assert!(matches!(error, Error::Timeout { .. }), "{error}");
+        drop(instrument);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn error_codes() {
+        assert!(device_error(0, Duration::ZERO).is_ok());
+        assert!(matches!(
+            device_error(ERROR_IO_TIMEOUT, Duration::ZERO),
+            Err(Error::Timeout { .. })
+        ));
+        assert_eq!(
+            device_error(11, Duration::ZERO).unwrap_err().to_string(),
+            "Transport error: VXI-11 error 11: device locked by another link"
+        );
+    }
+}
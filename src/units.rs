@@ -0,0 +1,287 @@
+use std::time::Duration;
+
+use crate::{
+    Error, Result, ScpiDeserialize, ScpiSerialize, read_while, scpi_enum, skip_whitespace,
+};
+
+/// Physical unit of a newtype with its SCPI suffix.
+pub trait Unit: Sized {
+    const SUFFIX: &'static str;
+
+    /// Serialize the value followed by the unit suffix, e.g. `1.5V`.
+    fn with_suffix(self) -> WithSuffix<Self> {
+        WithSuffix(self)
+    }
+}
+
+/// Unit value serialized with its suffix, see [`Unit::with_suffix`].
+///
+/// Deserialization accepts the value with or without a suffix, optionally separated
+/// by whitespace. A present suffix must match the unit, ignoring case, so `5.0HZ`
+/// fails as [`Volts`], catching a query mapped to the wrong type.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct WithSuffix<U>(pub U);
+
+impl<U: Unit + ScpiSerialize> ScpiSerialize for WithSuffix<U> {
+    fn serialize(&self, out: &mut String) {
+        self.0.serialize(out);
+        out.push_str(U::SUFFIX);
+    }
+}
+
+impl<U: Unit + ScpiDeserialize> ScpiDeserialize for WithSuffix<U> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let value = U::deserialize(input)?;
+
+        let mut cursor = *input;
+        skip_whitespace(&mut cursor);
+        let suffix = read_while(&mut cursor, |c| c.is_ascii_alphabetic());
+        if !suffix.is_empty() {
+            if !suffix.eq_ignore_ascii_case(U::SUFFIX) {
+                return Err(Error::ResponseDecoding(format!(
+                    "Expected unit `{}`, found `{suffix}`",
+                    U::SUFFIX
+                )));
+            }
+            *input = cursor;
+        }
+        Ok(Self(value))
+    }
+}
+
+macro_rules! impl_unit {
+    ($(#[$meta:meta])* $name:ident, $suffix:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+        pub struct $name(pub f64);
+
+        impl Unit for $name {
+            const SUFFIX: &'static str = $suffix;
+        }
+
+        impl From<f64> for $name {
+            fn from(value: f64) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for f64 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl ScpiSerialize for $name {
+            fn serialize(&self, out: &mut String) {
+                self.0.serialize(out);
+            }
+        }
+
+        impl ScpiDeserialize for $name {
+            fn deserialize(input: &mut &str) -> Result<Self> {
+                f64::deserialize(input).map(Self)
+            }
+        }
+    };
+}
+
+impl_unit!(
+    /// Voltage in volts.
+    Volts,
+    "V"
+);
+impl_unit!(
+    /// Current in amperes.
+    Amperes,
+    "A"
+);
+impl_unit!(
+    /// Frequency in hertz.
+    Hertz,
+    "HZ"
+);
+impl_unit!(
+    /// Time in seconds.
+    Seconds,
+    "S"
+);
+
+/// Durations are (de)serialized in seconds, e.g. `0.25`.
+impl ScpiSerialize for Duration {
+    fn serialize(&self, out: &mut String) {
+        self.as_secs_f64().serialize(out);
+    }
+}
+
+impl ScpiDeserialize for Duration {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut cursor = *input;
+        let duration = seconds_to_duration(f64::deserialize(&mut cursor)?)?;
+        *input = cursor;
+        Ok(duration)
+    }
+}
+
+fn seconds_to_duration(seconds: f64) -> Result<Duration> {
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|e| Error::ResponseDecoding(format!("Invalid duration `{seconds}` s: {e}")))
+}
+
+scpi_enum!(
+    /// Time unit suffix of a [`ScaledDuration`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum TimeUnit {
+        Milliseconds => "MS",
+        Microseconds => "US",
+        Seconds => "S",
+    }
+);
+
+impl TimeUnit {
+    /// Number of units per second.
+    pub fn per_second(&self) -> f64 {
+        match self {
+            TimeUnit::Seconds => 1.0,
+            TimeUnit::Milliseconds => 1e3,
+            TimeUnit::Microseconds => 1e6,
+        }
+    }
+}
+
+/// Duration serialized in the given unit followed by its suffix, e.g. `250MS`,
+/// for instruments requiring a unit.
+///
+/// Deserialization accepts any of the suffixes, a missing suffix means seconds.
+/// The unit is kept, so a round trip reproduces the response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScaledDuration(pub Duration, pub TimeUnit);
+
+impl ScpiSerialize for ScaledDuration {
+    fn serialize(&self, out: &mut String) {
+        (self.0.as_secs_f64() * self.1.per_second()).serialize(out);
+        self.1.serialize(out);
+    }
+}
+
+impl ScpiDeserialize for ScaledDuration {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut cursor = *input;
+        let value = f64::deserialize(&mut cursor)?;
+        skip_whitespace(&mut cursor);
+        let suffix = read_while(&mut cursor, |c| c.is_ascii_alphabetic());
+        let unit = if suffix.is_empty() {
+            TimeUnit::Seconds
+        } else {
+            TimeUnit::deserialize_complete(&suffix.to_ascii_uppercase()).map_err(|_| {
+                Error::ResponseDecoding(format!("Expected time unit, found `{suffix}`"))
+            })?
+        };
+        let duration = seconds_to_duration(value / unit.per_second())?;
+        *input = cursor;
+        Ok(Self(duration, unit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_units() {
+        assert_eq!(Volts(1.5).serialize_to_string(), "1.5");
+        assert_eq!(Volts(1.5).with_suffix().serialize_to_string(), "1.5V");
+        assert_eq!(Amperes(0.1).with_suffix().serialize_to_string(), "0.1A");
+        assert_eq!(Hertz(1e3).with_suffix().serialize_to_string(), "1000HZ");
+        assert_eq!(Seconds(2e-3).with_suffix().serialize_to_string(), "0.002S");
+    }
+
+    #[test]
+    fn deserialize_units() {
+        assert_eq!(Volts::deserialize_complete("1.5").unwrap(), Volts(1.5));
+        assert_eq!(Hertz::deserialize_complete("1E3").unwrap(), Hertz(1e3));
+        assert!(Seconds::deserialize_complete("x").is_err());
+    }
+
+    #[test]
+    fn deserialize_with_suffix() {
+        let parse = WithSuffix::<Volts>::deserialize_complete;
+        assert_eq!(parse("5.0V").unwrap(), WithSuffix(Volts(5.0)));
+        assert_eq!(parse("5.0 v").unwrap(), WithSuffix(Volts(5.0)));
+        assert_eq!(parse("5.0").unwrap(), WithSuffix(Volts(5.0)));
+        assert!(parse("5.0HZ").is_err());
+        assert!(parse("5.0VA").is_err());
+        assert_eq!(
+            WithSuffix::<Hertz>::deserialize_complete("1E3Hz").unwrap(),
+            WithSuffix(Hertz(1e3))
+        );
+        crate::assert_scpi_roundtrip!(Seconds(2e-3).with_suffix());
+    }
+
+    #[test]
+    fn duration() {
+        assert_eq!(Duration::from_millis(1500).serialize_to_string(), "1.5");
+        assert_eq!(Duration::from_millis(250).serialize_to_string(), "0.25");
+        assert_eq!(Duration::from_micros(2).serialize_to_string(), "0.000002");
+        assert_eq!(
+            Duration::deserialize_complete("1.5").unwrap(),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(
+            Duration::deserialize_complete("2E-6").unwrap(),
+            Duration::from_micros(2)
+        );
+        for duration in [
+            Duration::from_nanos(1),
+            Duration::from_nanos(123_456_789),
+            Duration::from_secs(86_400 * 365),
+        ] {
+            crate::assert_scpi_roundtrip!(duration);
+        }
+        assert!(Duration::deserialize_complete("-1").is_err());
+        assert!(Duration::deserialize_complete("INF").is_err());
+    }
+
+    #[test]
+    fn scaled_duration() {
+        let serialize = |duration, unit| ScaledDuration(duration, unit).serialize_to_string();
+        assert_eq!(
+            serialize(Duration::from_millis(1500), TimeUnit::Seconds),
+            "1.5S"
+        );
+        assert_eq!(
+            serialize(Duration::from_millis(250), TimeUnit::Milliseconds),
+            "250MS"
+        );
+        assert_eq!(
+            serialize(Duration::from_micros(2), TimeUnit::Microseconds),
+            "2US"
+        );
+
+        let parse = |response| ScaledDuration::deserialize_complete(response).unwrap();
+        assert_eq!(
+            parse("1.5"),
+            ScaledDuration(Duration::from_millis(1500), TimeUnit::Seconds)
+        );
+        assert_eq!(
+            parse("250 ms"),
+            ScaledDuration(Duration::from_millis(250), TimeUnit::Milliseconds)
+        );
+        assert_eq!(
+            parse("2US"),
+            ScaledDuration(Duration::from_micros(2), TimeUnit::Microseconds)
+        );
+        crate::assert_scpi_roundtrip!(ScaledDuration(
+            Duration::from_millis(250),
+            TimeUnit::Milliseconds
+        ));
+        assert!(ScaledDuration::deserialize_complete("2HZ").is_err());
+        TimeUnit::validate().unwrap();
+    }
+
+    #[test]
+    fn convert_units() {
+        let volts: Volts = 1.5.into();
+        let value: f64 = volts.into();
+        assert_eq!(value, 1.5);
+    }
+}
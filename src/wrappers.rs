@@ -0,0 +1,555 @@
+use std::marker::PhantomData;
+
+use crate::{
+    Error, Result, ScpiDeserialize, ScpiSerialize, match_header, match_literal, read_n_bytes,
+    skip_whitespace,
+};
+
+/// Token representing an absent value, see [`WithPlaceholder`].
+pub trait Placeholder {
+    const TOKEN: &'static str;
+}
+
+/// Placeholder for absent values represented by an empty field.
+pub struct EmptyField;
+
+impl Placeholder for EmptyField {
+    const TOKEN: &'static str = "";
+}
+
+/// Optional value, where `None` is represented by the token of `P`.
+///
+/// Deserialization yields `None` for the token and for an empty field,
+/// i.e. at the end of input or in front of a `,` or `;`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithPlaceholder<T, P: Placeholder = EmptyField> {
+    pub value: Option<T>,
+    placeholder: PhantomData<P>,
+}
+
+impl<T, P: Placeholder> WithPlaceholder<T, P> {
+    pub fn new(value: Option<T>) -> Self {
+        Self {
+            value,
+            placeholder: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> Option<T> {
+        self.value
+    }
+}
+
+impl<T, P: Placeholder> From<Option<T>> for WithPlaceholder<T, P> {
+    fn from(value: Option<T>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ScpiSerialize, P: Placeholder> ScpiSerialize for WithPlaceholder<T, P> {
+    fn serialize(&self, out: &mut String) {
+        match &self.value {
+            Some(inner) => inner.serialize(out),
+            None => out.push_str(P::TOKEN),
+        }
+    }
+}
+
+impl<T: ScpiDeserialize, P: Placeholder> ScpiDeserialize for WithPlaceholder<T, P> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        if input.is_empty() || input.starts_with([',', ';']) {
+            return Ok(Self::new(None));
+        }
+        if !P::TOKEN.is_empty() && match_literal(input, P::TOKEN).is_ok() {
+            return Ok(Self::new(None));
+        }
+        T::deserialize(input).map(|value| Self::new(Some(value)))
+    }
+}
+
+/// Opt-in leading whitespace tolerance, deserialization skips
+/// spaces and tabs before deserializing `T`.
+/// This works for primitives as well as enums defined with `scpi_enum!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SkipWhitespace<T>(pub T);
+
+impl<T: ScpiSerialize> ScpiSerialize for SkipWhitespace<T> {
+    fn serialize(&self, out: &mut String) {
+        self.0.serialize(out);
+    }
+}
+
+impl<T: ScpiDeserialize> ScpiDeserialize for SkipWhitespace<T> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        skip_whitespace(input);
+        T::deserialize(input).map(Self)
+    }
+}
+
+/// Header echoed by an instrument in front of a response value, see [`WithHeader`].
+pub trait Header {
+    const HEADER: &'static str;
+
+    /// Tolerate a missing or additional leading `:` in the response, see
+    /// [`match_header`]. Disabled by default, matching the header exactly.
+    const OPTIONAL_ROOT: bool = false;
+}
+
+/// Value preceded by the header of `H` and a separating space, e.g. `:VOLT 1.5`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithHeader<H: Header, T> {
+    pub value: T,
+    header: PhantomData<H>,
+}
+
+impl<H: Header, T> WithHeader<H, T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            header: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<H: Header, T: ScpiSerialize> ScpiSerialize for WithHeader<H, T> {
+    fn serialize(&self, out: &mut String) {
+        out.push_str(H::HEADER);
+        out.push(' ');
+        self.value.serialize(out);
+    }
+}
+
+impl<H: Header, T: ScpiDeserialize> ScpiDeserialize for WithHeader<H, T> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        if H::OPTIONAL_ROOT {
+            match_header(input, H::HEADER)?;
+        } else {
+            match_literal(input, H::HEADER)?;
+        }
+        match_literal(input, " ")?;
+        T::deserialize(input).map(Self::new)
+    }
+}
+
+/// Expression data enclosed in parentheses, e.g. `(1,2,3)` with `Vec<T>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parenthesized<T>(pub T);
+
+impl<T: ScpiSerialize> ScpiSerialize for Parenthesized<T> {
+    fn serialize(&self, out: &mut String) {
+        out.push('(');
+        self.0.serialize(out);
+        out.push(')');
+    }
+}
+
+impl<T: ScpiDeserialize> ScpiDeserialize for Parenthesized<T> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        match_literal(input, "(")?;
+        let inner = T::deserialize(input)?;
+        match_literal(input, ")").map_err(|_| {
+            Error::ResponseDecoding(format!("Expected closing `)` in front of `{input}`"))
+        })?;
+        Ok(Self(inner))
+    }
+}
+
+/// Value enclosed in arbitrary framing characters, e.g. `<1.5>` with `Framed<'<', '>', f64>`.
+///
+/// Deserialization fails without consuming input if either character is missing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Framed<const OPEN: char, const CLOSE: char, T>(pub T);
+
+impl<const OPEN: char, const CLOSE: char, T: ScpiSerialize> ScpiSerialize
+    for Framed<OPEN, CLOSE, T>
+{
+    fn serialize(&self, out: &mut String) {
+        out.push(OPEN);
+        self.0.serialize(out);
+        out.push(CLOSE);
+    }
+
+    fn serialized_len(&self) -> usize {
+        OPEN.len_utf8() + self.0.serialized_len() + CLOSE.len_utf8()
+    }
+}
+
+impl<const OPEN: char, const CLOSE: char, T: ScpiDeserialize> ScpiDeserialize
+    for Framed<OPEN, CLOSE, T>
+{
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut cursor = input.strip_prefix(OPEN).ok_or_else(|| {
+            Error::ResponseDecoding(format!("Expected opening `{OPEN}` in front of `{input}`"))
+        })?;
+        let inner = T::deserialize(&mut cursor)?;
+        cursor = cursor.strip_prefix(CLOSE).ok_or_else(|| {
+            Error::ResponseDecoding(format!("Expected closing `{CLOSE}` in front of `{cursor}`"))
+        })?;
+        *input = cursor;
+        Ok(Self(inner))
+    }
+}
+
+/// Optional trailing parameter including its leading separator, e.g. `,0.001` or nothing.
+///
+/// Unlike a bare `Option<T>` between literal separators, an omitted value does not leave
+/// a dangling separator, e.g. `MEAS:VOLT? 10,`. It must only be used for the last parameters,
+/// an omitted parameter in the middle would shift the meaning of the following ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OptionalTrailing<T, const SEPARATOR: char = ','>(pub Option<T>);
+
+impl<T, const SEPARATOR: char> From<Option<T>> for OptionalTrailing<T, SEPARATOR> {
+    fn from(value: Option<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: ScpiSerialize, const SEPARATOR: char> ScpiSerialize for OptionalTrailing<T, SEPARATOR> {
+    fn serialize(&self, out: &mut String) {
+        if let Some(value) = &self.0 {
+            out.push(SEPARATOR);
+            value.serialize(out);
+        }
+    }
+}
+
+/// `None` at the end of input, otherwise the separator followed by `T`.
+impl<T: ScpiDeserialize, const SEPARATOR: char> ScpiDeserialize for OptionalTrailing<T, SEPARATOR> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        if input.is_empty() {
+            return Ok(Self(None));
+        }
+        let Some(rest) = input.strip_prefix(SEPARATOR) else {
+            return Err(Error::ResponseDecoding(format!(
+                "Expected `{SEPARATOR}` or end of input, not matched `{input}`"
+            )));
+        };
+        *input = rest;
+        T::deserialize(input).map(|value| Self(Some(value)))
+    }
+}
+
+/// One of two alternative response shapes, e.g. a number or a keyword.
+///
+/// Deserialization tries `A` first and falls back to `B`, input is only
+/// consumed by the alternative that succeeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+impl<A: ScpiSerialize, B: ScpiSerialize> ScpiSerialize for Either<A, B> {
+    fn serialize(&self, out: &mut String) {
+        match self {
+            Either::Left(value) => value.serialize(out),
+            Either::Right(value) => value.serialize(out),
+        }
+    }
+}
+
+impl<A: ScpiDeserialize, B: ScpiDeserialize> ScpiDeserialize for Either<A, B> {
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut cursor = *input;
+        if let Ok(value) = A::deserialize(&mut cursor) {
+            *input = cursor;
+            return Ok(Either::Left(value));
+        }
+        let mut cursor = *input;
+        let value = B::deserialize(&mut cursor)?;
+        *input = cursor;
+        Ok(Either::Right(value))
+    }
+}
+
+/// Field of exactly `WIDTH` bytes padded with spaces, as returned by some legacy instruments.
+///
+/// Values are right-aligned, i.e. padded on the left, or left-aligned with `ALIGN_LEFT`.
+/// A value longer than the width is serialized unpadded. Deserialization reads exactly
+/// `WIDTH` bytes, trims surrounding spaces and parses `T` from the rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FixedWidth<T, const WIDTH: usize, const ALIGN_LEFT: bool = false>(pub T);
+
+impl<T: ScpiSerialize, const WIDTH: usize, const ALIGN_LEFT: bool> ScpiSerialize
+    for FixedWidth<T, WIDTH, ALIGN_LEFT>
+{
+    fn serialize(&self, out: &mut String) {
+        let padding = " ".repeat(WIDTH.saturating_sub(self.0.serialized_len()));
+        if !ALIGN_LEFT {
+            out.push_str(&padding);
+        }
+        self.0.serialize(out);
+        if ALIGN_LEFT {
+            out.push_str(&padding);
+        }
+    }
+
+    fn serialized_len(&self) -> usize {
+        self.0.serialized_len().max(WIDTH)
+    }
+}
+
+impl<T: ScpiDeserialize, const WIDTH: usize, const ALIGN_LEFT: bool> ScpiDeserialize
+    for FixedWidth<T, WIDTH, ALIGN_LEFT>
+{
+    fn deserialize(input: &mut &str) -> Result<Self> {
+        let mut cursor = *input;
+        let field = read_n_bytes(&mut cursor, WIDTH)?;
+        let value = T::deserialize_complete(field.trim_matches(' '))?;
+        *input = cursor;
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{check_empty, read_until};
+
+    struct NoValue;
+
+    #[test]
+    fn fixed_width() {
+        assert_eq!(FixedWidth::<_, 6>(1.5).serialize_to_string(), "   1.5");
+        assert_eq!(
+            FixedWidth::<_, 6, true>(1.5).serialize_to_string(),
+            "1.5   "
+        );
+        assert_eq!(FixedWidth::<_, 3>(123).serialize_to_string(), "123");
+        assert_eq!(FixedWidth::<_, 2>(123).serialize_to_string(), "123");
+
+        let input = &mut "   1.5-2.25 123";
+        assert_eq!(FixedWidth::<f64, 6>::deserialize(input).unwrap().0, 1.5);
+        assert_eq!(FixedWidth::<f64, 6>::deserialize(input).unwrap().0, -2.25);
+        assert_eq!(FixedWidth::<u8, 3>::deserialize(input).unwrap().0, 123);
+        assert!(check_empty(input).is_ok());
+
+        let parse = FixedWidth::<u8, 5, true>::deserialize_complete;
+        assert_eq!(parse("12   ").unwrap().0, 12);
+        assert_eq!(parse(" 12  ").unwrap().0, 12);
+        assert!(parse("12").is_err());
+        assert!(parse("1 2  ").is_err());
+        crate::assert_scpi_roundtrip!(FixedWidth::<_, 8, true>(-4i32));
+    }
+
+    impl Placeholder for NoValue {
+        const TOKEN: &'static str = "NONE";
+    }
+
+    #[test]
+    fn serialize_placeholder() {
+        let value: WithPlaceholder<u32, NoValue> = Some(12).into();
+        assert_eq!(value.serialize_to_string(), "12");
+        let value: WithPlaceholder<u32, NoValue> = None.into();
+        assert_eq!(value.serialize_to_string(), "NONE");
+        let value: WithPlaceholder<u32> = None.into();
+        assert_eq!(value.serialize_to_string(), "");
+    }
+
+    #[test]
+    fn deserialize_placeholder() {
+        let value = WithPlaceholder::<u32, NoValue>::deserialize_complete("NONE").unwrap();
+        assert_eq!(value.into_inner(), None);
+        let value = WithPlaceholder::<u32, NoValue>::deserialize_complete("").unwrap();
+        assert_eq!(value.into_inner(), None);
+        let value = WithPlaceholder::<u32, NoValue>::deserialize_complete("12").unwrap();
+        assert_eq!(value.into_inner(), Some(12));
+        assert!(WithPlaceholder::<u32, NoValue>::deserialize_complete("NIL").is_err());
+    }
+
+    #[test]
+    fn deserialize_empty_field() {
+        let input = &mut ",12";
+        let first = WithPlaceholder::<u32>::deserialize(input).unwrap();
+        assert_eq!(first.into_inner(), None);
+        read_until(input, ',').unwrap();
+        let second = WithPlaceholder::<u32>::deserialize(input).unwrap();
+        assert_eq!(second.into_inner(), Some(12));
+        assert!(check_empty(input).is_ok());
+    }
+
+    #[test]
+    fn deserialize_skip_whitespace() {
+        assert_eq!(
+            SkipWhitespace::<u32>::deserialize_complete(" 123").unwrap(),
+            SkipWhitespace(123)
+        );
+        assert_eq!(
+            SkipWhitespace::<f32>::deserialize_complete("\t-1.5").unwrap(),
+            SkipWhitespace(-1.5)
+        );
+        assert!(u32::deserialize_complete(" 123").is_err());
+    }
+
+    struct VoltageHeader;
+
+    impl Header for VoltageHeader {
+        const HEADER: &'static str = ":VOLT";
+    }
+
+    #[test]
+    fn serialize_header() {
+        let value = WithHeader::<VoltageHeader, f32>::new(1.5);
+        assert_eq!(value.serialize_to_string(), ":VOLT 1.5");
+    }
+
+    #[test]
+    fn deserialize_header() {
+        let value = WithHeader::<VoltageHeader, f32>::deserialize_complete(":VOLT 1.5").unwrap();
+        assert_eq!(value.into_inner(), 1.5);
+        assert!(WithHeader::<VoltageHeader, f32>::deserialize_complete(":CURR 1.5").is_err());
+        assert!(WithHeader::<VoltageHeader, f32>::deserialize_complete(":VOLT1.5").is_err());
+        assert!(WithHeader::<VoltageHeader, f32>::deserialize_complete("1.5").is_err());
+        assert!(WithHeader::<VoltageHeader, f32>::deserialize_complete("VOLT 1.5").is_err());
+    }
+
+    struct CurrentHeader;
+
+    impl Header for CurrentHeader {
+        const HEADER: &'static str = "CURR";
+        const OPTIONAL_ROOT: bool = true;
+    }
+
+    #[test]
+    fn deserialize_header_optional_root() {
+        for response in ["CURR 0.1", ":CURR 0.1"] {
+            let value = WithHeader::<CurrentHeader, f32>::deserialize_complete(response).unwrap();
+            assert_eq!(value.into_inner(), 0.1);
+        }
+        assert!(WithHeader::<CurrentHeader, f32>::deserialize_complete("::CURR 0.1").is_err());
+        assert!(WithHeader::<CurrentHeader, f32>::deserialize_complete(":VOLT 0.1").is_err());
+        let value = WithHeader::<CurrentHeader, f32>::new(0.1);
+        assert_eq!(value.serialize_to_string(), "CURR 0.1");
+    }
+
+    #[test]
+    fn serialize_parenthesized() {
+        assert_eq!(Parenthesized(12u8).serialize_to_string(), "(12)");
+        assert_eq!(
+            Parenthesized(vec![1u8, 2, 3]).serialize_to_string(),
+            "(1,2,3)"
+        );
+    }
+
+    #[test]
+    fn deserialize_parenthesized() {
+        assert_eq!(
+            Parenthesized::<Vec<u8>>::deserialize_complete("(1,2,3)").unwrap(),
+            Parenthesized(vec![1, 2, 3])
+        );
+        assert_eq!(
+            Parenthesized::<f32>::deserialize_complete("(-1.5)").unwrap(),
+            Parenthesized(-1.5)
+        );
+        assert!(Parenthesized::<Vec<u8>>::deserialize_complete("(1,2,3").is_err());
+        assert!(Parenthesized::<Vec<u8>>::deserialize_complete("1,2,3)").is_err());
+    }
+
+    #[test]
+    fn framed() {
+        assert_eq!(Framed::<'<', '>', _>(1.5).serialize_to_string(), "<1.5>");
+        assert_eq!(Framed::<'[', ']', _>(-2i8).serialized_len(), 4);
+        assert_eq!(
+            Framed::<'<', '>', f64>::deserialize_complete("<1.5>").unwrap(),
+            Framed(1.5)
+        );
+        assert_eq!(
+            Framed::<'[', ']', u16>::deserialize_complete("[42]").unwrap(),
+            Framed(42)
+        );
+        crate::assert_scpi_roundtrip!(Framed::<'[', ']', _>(vec![1u8, 2]));
+
+        for malformed in ["1.5>", "<1.5", "[1.5]", "<>"] {
+            let input = &mut &*malformed;
+            assert!(Framed::<'<', '>', f64>::deserialize(input).is_err());
+            assert_eq!(*input, malformed);
+        }
+    }
+
+    struct Measure {
+        range: Option<f32>,
+        resolution: Option<f32>,
+    }
+    crate::impl_scpi_serialize!(Measure, ["MEAS:VOLT? ", range, ",", resolution]);
+
+    struct MeasureTrailing {
+        range: OptionalTrailing<f32, ' '>,
+        resolution: OptionalTrailing<f32>,
+    }
+    crate::impl_scpi_serialize!(MeasureTrailing, ["MEAS:VOLT?", range, resolution]);
+
+    #[test]
+    fn serialize_optional_trailing() {
+        // a bare `Option` leaves its separators behind
+        let command = Measure {
+            range: Some(10.0),
+            resolution: None,
+        };
+        assert_eq!(command.serialize_to_string(), "MEAS:VOLT? 10,");
+        let command = Measure {
+            range: None,
+            resolution: Some(0.001),
+        };
+        assert_eq!(command.serialize_to_string(), "MEAS:VOLT? ,0.001");
+
+        let command = MeasureTrailing {
+            range: Some(10.0).into(),
+            resolution: None.into(),
+        };
+        assert_eq!(command.serialize_to_string(), "MEAS:VOLT? 10");
+        let command = MeasureTrailing {
+            range: Some(10.0).into(),
+            resolution: Some(0.001).into(),
+        };
+        assert_eq!(command.serialize_to_string(), "MEAS:VOLT? 10,0.001");
+        let command = MeasureTrailing {
+            range: None.into(),
+            resolution: None.into(),
+        };
+        assert_eq!(command.serialize_to_string(), "MEAS:VOLT?");
+    }
+
+    #[test]
+    fn deserialize_optional_trailing() {
+        let input = &mut "1,2";
+        assert_eq!(u8::deserialize(input).unwrap(), 1);
+        assert_eq!(
+            OptionalTrailing::<u8>::deserialize(input).unwrap(),
+            OptionalTrailing(Some(2))
+        );
+        assert_eq!(
+            OptionalTrailing::<u8>::deserialize(input).unwrap(),
+            OptionalTrailing(None)
+        );
+        assert!(OptionalTrailing::<u8>::deserialize_complete(";2").is_err());
+    }
+
+    #[test]
+    fn either() {
+        type Level = Either<f32, crate::primitives::Boolean>;
+        assert_eq!(
+            Level::deserialize_complete("1.5").unwrap(),
+            Either::Left(1.5)
+        );
+        assert_eq!(
+            Level::deserialize_complete("OFF").unwrap(),
+            Either::Right(crate::primitives::Boolean(false))
+        );
+        assert_eq!(Either::<f32, u8>::Left(1.5).serialize_to_string(), "1.5");
+        assert_eq!(Either::<f32, u8>::Right(3).serialize_to_string(), "3");
+
+        // `A` matches the prefix `(1` and fails, `B` starts over
+        let input = &mut "(1;2)";
+        let value = Either::<Parenthesized<u8>, String>::deserialize(input).unwrap();
+        assert_eq!(value, Either::Right("(1;2)".to_string()));
+        assert!(input.is_empty());
+
+        let input = &mut "X";
+        assert!(Either::<u8, Parenthesized<u8>>::deserialize(input).is_err());
+        assert_eq!(*input, "X");
+    }
+}
@@ -0,0 +1,120 @@
+//! Scripted in-process TCP server for driving transports end-to-end.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+enum Step {
+    Expect(String),
+    Reply(Vec<u8>),
+    Pause(Duration),
+}
+
+/// Exchanges to perform, one list of steps per accepted connection.
+pub struct Script {
+    connections: Vec<Vec<Step>>,
+}
+
+impl Script {
+    pub fn new() -> Self {
+        Self {
+            connections: vec![Vec::new()],
+        }
+    }
+
+    fn push(mut self, step: Step) -> Self {
+        self.connections.last_mut().unwrap().push(step);
+        self
+    }
+
+    /// Read one line, including its `\n`, and assert it equals `line`.
+    pub fn expect(self, line: &str) -> Self {
+        self.push(Step::Expect(line.to_string()))
+    }
+
+    /// Write `data` in one segment.
+    pub fn reply(self, data: impl AsRef<[u8]>) -> Self {
+        self.push(Step::Reply(data.as_ref().to_vec()))
+    }
+
+    /// Write `data` in segments of `size` bytes, pausing briefly in between
+    /// so the client most likely receives them in separate reads.
+    pub fn reply_chunked(mut self, data: impl AsRef<[u8]>, size: usize) -> Self {
+        for chunk in data.as_ref().chunks(size) {
+            self = self.reply(chunk).pause(Duration::from_millis(1));
+        }
+        self
+    }
+
+    pub fn pause(self, duration: Duration) -> Self {
+        self.push(Step::Pause(duration))
+    }
+
+    /// Close the current connection and accept the next one.
+    pub fn disconnect(mut self) -> Self {
+        self.connections.push(Vec::new());
+        self
+    }
+}
+
+/// Server running a [`Script`] on a background thread.
+pub struct MockServer {
+    address: SocketAddr,
+    handle: thread::JoinHandle<()>,
+}
+
+impl MockServer {
+    pub fn start(script: Script) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let handle = thread::spawn(move || {
+            let last = script.connections.len() - 1;
+            for (index, steps) in script.connections.into_iter().enumerate() {
+                let (stream, _) = listener.accept().unwrap();
+                run(stream, steps, index == last);
+            }
+        });
+        Self { address, handle }
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Wait for the script to complete, the client must be dropped before,
+    /// as the last connection is served until the client closes it.
+    /// Panics of the server, e.g. unexpected messages, are propagated.
+    pub fn finish(self) {
+        if let Err(panic) = self.handle.join() {
+            std::panic::resume_unwind(panic);
+        }
+    }
+}
+
+fn run(mut stream: TcpStream, steps: Vec<Step>, last: bool) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    for step in steps {
+        match step {
+            Step::Expect(expected) => {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                assert_eq!(line, expected, "unexpected message");
+            }
+            Step::Reply(data) => {
+                stream.write_all(&data).unwrap();
+                stream.flush().unwrap();
+            }
+            Step::Pause(duration) => thread::sleep(duration),
+        }
+    }
+    if last {
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert!(
+            rest.is_empty(),
+            "unexpected trailing messages: {:?}",
+            String::from_utf8_lossy(&rest)
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! End-to-end tests of [`TcpInstrument`] against a scripted server.
+
+mod common;
+
+use std::time::Duration;
+
+use common::{MockServer, Script};
+use scpi_client::batch::CommandScript;
+use scpi_client::block::read_block;
+use scpi_client::commands::{OutputState, OutputStateQuery};
+use scpi_client::primitives::Boolean;
+use scpi_client::transport::{Connection, InstrumentBuilder, TcpInstrument, Terminator};
+use scpi_client::{Error, ScpiDeserialize, ScpiRequest, impl_scpi_request, impl_scpi_serialize};
+
+struct VoltageQuery;
+impl_scpi_serialize!(VoltageQuery, ["SOUR:VOLT?"]);
+impl_scpi_request!(VoltageQuery, f64);
+
+fn assert_query<C: Connection, R: ScpiRequest>(
+    instrument: &mut C,
+    request: &R,
+    expected: R::Response,
+) where
+    R::Response: ScpiDeserialize + PartialEq + std::fmt::Debug,
+{
+    assert_eq!(instrument.query(request).unwrap(), expected);
+}
+
+#[test]
+fn segmented_responses() {
+    let server = MockServer::start(
+        Script::new()
+            .expect("SOUR:VOLT?\r\n")
+            .reply_chunked("1.25\r\n", 1)
+            .expect("SOUR:VOLT?\r\n")
+            .reply("2.5\r\n"),
+    );
+    let mut instrument = InstrumentBuilder::new()
+        .terminator(Terminator::CrLf)
+        .read_buffer_size(3)
+        .connect(server.address())
+        .unwrap();
+    assert_query(&mut instrument, &VoltageQuery, 1.25);
+    assert_query(&mut instrument, &VoltageQuery, 2.5);
+    drop(instrument);
+    server.finish();
+}
+
+#[test]
+fn large_block() {
+    let payload: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    let mut response = format!("#6{:06}", payload.len()).into_bytes();
+    response.extend_from_slice(&payload);
+    response.push(b'\n');
+
+    let server = MockServer::start(
+        Script::new()
+            .expect("CURV?\n")
+            .reply_chunked(&response, 8192),
+    );
+    let mut instrument = InstrumentBuilder::new()
+        .read_buffer_size(1000)
+        .connect(server.address())
+        .unwrap();
+    instrument.write_str("CURV?").unwrap();
+    let message = instrument.receive_bytes().unwrap();
+    let input = &mut message.as_slice();
+    assert_eq!(read_block(input, false).unwrap(), payload.as_slice());
+    assert!(input.is_empty());
+    drop(instrument);
+    server.finish();
+}
+
+struct CurrentQuery;
+impl_scpi_serialize!(CurrentQuery, ["SOUR:CURR?"]);
+impl_scpi_request!(CurrentQuery, f64);
+
+struct OutputQuery;
+impl_scpi_serialize!(OutputQuery, ["OUTP?"]);
+impl_scpi_request!(OutputQuery, Boolean);
+
+#[test]
+fn query_batch() {
+    let server = MockServer::start(
+        Script::new()
+            .expect("SOUR:VOLT?;SOUR:CURR?;OUTP?\n")
+            .reply_chunked("+1.5E+00;+1.0E-01;1\n", 4),
+    );
+    let mut instrument = TcpInstrument::connect(server.address()).unwrap();
+    let (voltage, current, output) = instrument
+        .query_batch((VoltageQuery, CurrentQuery, OutputQuery))
+        .unwrap();
+    assert_eq!((voltage, current, output), (1.5, 0.1, Boolean(true)));
+    drop(instrument);
+    server.finish();
+}
+
+#[test]
+fn output_state() {
+    let server = MockServer::start(
+        Script::new()
+            .expect("OUTP2:STAT ON\n")
+            .expect("OUTP2:STAT?\n")
+            .reply("1\n")
+            .expect("OUTP3:STAT?\n")
+            .reply("OFF\n"),
+    );
+    let mut instrument = TcpInstrument::connect(server.address()).unwrap();
+    instrument
+        .execute(&OutputState {
+            channel: 2,
+            state: Boolean(true),
+        })
+        .unwrap();
+    assert_query(
+        &mut instrument,
+        &OutputStateQuery { channel: 2 },
+        Boolean(true),
+    );
+    assert_query(
+        &mut instrument,
+        &OutputStateQuery { channel: 3 },
+        Boolean(false),
+    );
+    drop(instrument);
+    server.finish();
+}
+
+#[test]
+fn timeout() {
+    let server = MockServer::start(
+        Script::new()
+            .expect("SOUR:VOLT?\n")
+            .pause(Duration::from_millis(300))
+            .reply("1.5\n"),
+    );
+    let mut instrument = InstrumentBuilder::new()
+        .timeout(Duration::from_millis(100))
+        .connect(server.address())
+        .unwrap();
+    let error = instrument.query(&VoltageQuery).unwrap_err();
+    assert!(matches!(error, Error::Timeout { .. }), "{error}");
+    // the late response is still received
+    instrument.set_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(instrument.receive().unwrap(), "1.5");
+    drop(instrument);
+    server.finish();
+}
+
+#[test]
+fn reconnect() {
+    let server = MockServer::start(
+        Script::new()
+            .expect("SOUR:VOLT?\n")
+            .reply("1.5\n")
+            .disconnect()
+            .expect("SOUR:VOLT?\n")
+            .reply("2.5\n"),
+    );
+    let mut instrument = InstrumentBuilder::new()
+        .reconnect(true)
+        .connect(server.address())
+        .unwrap();
+    assert_query(&mut instrument, &VoltageQuery, 1.5);
+    assert_query(&mut instrument, &VoltageQuery, 2.5);
+    drop(instrument);
+    server.finish();
+}
+
+#[test]
+fn connect_default() {
+    let server = MockServer::start(Script::new().expect("*RST\n"));
+    let mut instrument = TcpInstrument::connect(server.address()).unwrap();
+    instrument.write_str("*RST").unwrap();
+    drop(instrument);
+    server.finish();
+}
+
+#[test]
+fn empty_response() {
+    let server = MockServer::start(
+        Script::new()
+            .expect("SOUR:VOLT?\n")
+            .reply("\n")
+            .expect("SOUR:VOLT?;SOUR:CURR?\n")
+            .reply("\n")
+            .expect("*IDN?\n")
+            .reply("\n"),
+    );
+    let mut instrument = InstrumentBuilder::new()
+        .empty_response_error(true)
+        .connect(server.address())
+        .unwrap();
+    assert!(matches!(
+        instrument.query(&VoltageQuery),
+        Err(Error::EmptyResponse)
+    ));
+    assert!(matches!(
+        instrument.query_batch((VoltageQuery, CurrentQuery)),
+        Err(Error::EmptyResponse)
+    ));
+    assert_eq!(instrument.query_str("*IDN?").unwrap(), "");
+    drop(instrument);
+    server.finish();
+}
+
+#[test]
+fn echo_script() {
+    let mut script = Script::new();
+    for line in ["*CLS\n", "VOLT 1\n", "OUTP ON\n"] {
+        script = script.expect(line).reply(line);
+    }
+    let server = MockServer::start(script.expect("SOUR:VOLT?\n").reply("SOUR:VOLT?\n1\n"));
+    let mut instrument = InstrumentBuilder::new()
+        .echo(true)
+        .clear_status_before_batch(true)
+        .connect(server.address())
+        .unwrap();
+    let script = CommandScript::new().command("VOLT 1").command("OUTP ON");
+    instrument.execute_script(&script).unwrap();
+    assert_query(&mut instrument, &VoltageQuery, 1.0);
+    drop(instrument);
+    server.finish();
+}